@@ -1,19 +1,11 @@
-#![feature(specialization)]
-
 use structnom::*;
 
-// #[macro_use]
-// extern crate nom;
-
-use nom::*;
-use nom::{le_u8, le_u32};
-
-generate_structnom!(little);
+use nom::number::complete::{be_u16, le_u32, le_u8};
 
 
 static SOME_SLICE: &[u8] = &[1, 2, 3, 4];
 
-#[derive(StructNom)]
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
 #[snom(switch = le_u8)]
 pub enum Instr {
     #[snom(range(start = 1))]
@@ -34,7 +26,7 @@ pub enum Instr {
 // #[derive(StructNom)]
 // pub struct Example<T: StructNom> {
 //     // #[snom(debug = "0x{:x?}")]
-//     #[snom(parser = nom::le_u32)]
+//     #[snom(parser = nom::number::complete::le_u32)]
 //     foo: u32,
 //     #[snom(skip)]
 //     bar: Vec<T>,
@@ -54,9 +46,48 @@ pub enum Instr {
 pub struct MyStruct {
     #[snom(call(le_u32))]
     first: u32,
+    #[snom(tag(SOME_SLICE))]
     last: u64,
 }
 
+fn be_u16_prefixed(input: &[u8]) -> nom::IResult<&[u8], Vec<u8>> {
+    let (input, length) = be_u16(input)?;
+
+    nom::multi::count(le_u8, length as usize)(input)
+}
+
+fn write_be_u16_prefixed<W: std::io::Write>(bytes: &[u8], out: &mut W) -> std::io::Result<()> {
+    out.write_all(&(bytes.len() as u16).to_be_bytes())?;
+    out.write_all(bytes)
+}
+
+// Collections use the blanket `Vec<T>` impl unless a field overrides it.
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+pub struct Payload {
+    #[snom(parser = be_u16_prefixed)]
+    #[snom(writer = write_be_u16_prefixed)]
+    data: Vec<u8>,
+    instrs: Vec<Instr>,
+}
+
 fn main() {
+    let mut bytes = Vec::new();
+    Instr::Skip3.write(&mut bytes).unwrap();
+
+    assert_eq!(Instr::nom(&bytes).unwrap().1, Instr::Skip3);
+
+    let payload = Payload {
+        data: vec![0xAA; 300],
+        instrs: vec![Instr::Nop, Instr::Another],
+    };
+
+    let mut bytes = Vec::new();
+    payload.write(&mut bytes).unwrap();
+
+    assert_eq!(Payload::nom(&bytes).unwrap().1, payload);
+
+    let bytes = [0, 0, 0, 0, 1, 0, 0, 0, 1, 2, 3, 4, 2, 0, 0, 0, 0, 0, 0, 0];
+    let (_, my_struct) = MyStruct::nom(&bytes).unwrap();
 
+    assert_eq!((my_struct.first, my_struct.last), (1, 2));
 }
\ No newline at end of file
@@ -1,12 +1,8 @@
-// #![feature(specialization)]
-
-// use nom::*;
-// use structnom_derive::*;
-
-// generate_structnom!(big);
+// use structnom::*;
 
 
 // #[derive(Debug, StructNom)]
+// #[snom(endian = big)]
 // pub struct Ipv4Header {
 //     version_and_length: u8
 //     #[snom(bits(6))]
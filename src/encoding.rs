@@ -0,0 +1,171 @@
+//! Text encodings for `String` fields, picked with `#[snom(encoding = ..)]`.
+//!
+//! A string field is read as a `Vec` of its encoding's code units, so
+//! `length`, `count` and `iter` count code units rather than bytes. The
+//! units are then decoded into a `String`.
+//!
+//! An `until` terminator is still a run of raw bytes, looked for in front of
+//! every unit. A UTF-16 string ending in a zero unit needs `until = [0, 0]`,
+//! a lone `[0]` would also stop on the first byte of U+0100 in little endian.
+
+use std::convert::TryFrom;
+use std::io::{self, Write};
+
+use nom::number::complete as number;
+use nom::IResult;
+
+use crate::{Endian, StructNom, StructNomWrite};
+
+/// Converts between a `String` and the code units it is stored as.
+pub trait Encoding {
+    type Unit: for<'a> StructNom<'a> + StructNomWrite;
+
+    /// `None` if `units` are not valid in this encoding.
+    fn decode(units: &[Self::Unit]) -> Option<String>;
+
+    /// `None` if `text` has characters this encoding cannot represent.
+    fn encode(text: &str) -> Option<Vec<Self::Unit>>;
+}
+
+pub struct Utf8;
+
+impl Encoding for Utf8 {
+    type Unit = u8;
+
+    fn decode(units: &[u8]) -> Option<String> {
+        String::from_utf8(units.to_vec()).ok()
+    }
+
+    fn encode(text: &str) -> Option<Vec<u8>> {
+        Some(text.as_bytes().to_vec())
+    }
+}
+
+pub struct Ascii;
+
+impl Encoding for Ascii {
+    type Unit = u8;
+
+    fn decode(units: &[u8]) -> Option<String> {
+        if units.is_ascii() {
+            Utf8::decode(units)
+        } else {
+            None
+        }
+    }
+
+    fn encode(text: &str) -> Option<Vec<u8>> {
+        if text.is_ascii() {
+            Utf8::encode(text)
+        } else {
+            None
+        }
+    }
+}
+
+/// ISO 8859-1, every byte is the code point of the same value.
+pub struct Latin1;
+
+impl Encoding for Latin1 {
+    type Unit = u8;
+
+    fn decode(units: &[u8]) -> Option<String> {
+        Some(units.iter().map(|&unit| char::from(unit)).collect())
+    }
+
+    fn encode(text: &str) -> Option<Vec<u8>> {
+        text.chars()
+            .map(|c| u8::try_from(u32::from(c)).ok())
+            .collect()
+    }
+}
+
+pub struct Utf16Le;
+
+impl Encoding for Utf16Le {
+    type Unit = U16Le;
+
+    fn decode(units: &[U16Le]) -> Option<String> {
+        String::from_utf16(&units.iter().map(|unit| unit.0).collect::<Vec<_>>()).ok()
+    }
+
+    fn encode(text: &str) -> Option<Vec<U16Le>> {
+        Some(text.encode_utf16().map(U16Le).collect())
+    }
+}
+
+pub struct Utf16Be;
+
+impl Encoding for Utf16Be {
+    type Unit = U16Be;
+
+    fn decode(units: &[U16Be]) -> Option<String> {
+        String::from_utf16(&units.iter().map(|unit| unit.0).collect::<Vec<_>>()).ok()
+    }
+
+    fn encode(text: &str) -> Option<Vec<U16Be>> {
+        Some(text.encode_utf16().map(U16Be).collect())
+    }
+}
+
+/// A UTF-16LE code unit, little endian whatever the caller asks for.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct U16Le(pub u16);
+
+/// A UTF-16BE code unit, big endian whatever the caller asks for.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct U16Be(pub u16);
+
+impl<'a> StructNom<'a> for U16Le {
+    fn nom_endian(input: &'a [u8], _: Endian) -> IResult<&'a [u8], Self> {
+        let (input, unit) = number::le_u16(input)?;
+
+        Ok((input, U16Le(unit)))
+    }
+}
+
+impl StructNomWrite for U16Le {
+    fn write_endian<W: Write>(&self, out: &mut W, _: Endian) -> io::Result<()> {
+        out.write_all(&self.0.to_le_bytes())
+    }
+}
+
+impl<'a> StructNom<'a> for U16Be {
+    fn nom_endian(input: &'a [u8], _: Endian) -> IResult<&'a [u8], Self> {
+        let (input, unit) = number::be_u16(input)?;
+
+        Ok((input, U16Be(unit)))
+    }
+}
+
+impl StructNomWrite for U16Be {
+    fn write_endian<W: Write>(&self, out: &mut W, _: Endian) -> io::Result<()> {
+        out.write_all(&self.0.to_be_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrips() {
+        assert_eq!(Latin1::decode(&[0x63, 0x61, 0x66, 0xE9]).unwrap(), "café");
+        assert_eq!(Latin1::encode("café").unwrap(), [0x63, 0x61, 0x66, 0xE9]);
+        assert!(Latin1::encode("€").is_none());
+
+        assert!(Ascii::decode(&[0xE9]).is_none());
+        assert!(Utf8::decode(&[0xFF]).is_none());
+
+        let units = Utf16Be::encode("hé").unwrap();
+        let mut out = Vec::new();
+
+        for unit in &units {
+            unit.write(&mut out).unwrap();
+        }
+
+        assert_eq!(out, [0, b'h', 0, 0xE9]);
+        assert_eq!(Utf16Be::decode(&units).unwrap(), "hé");
+        assert!(Utf16Le::decode(&[U16Le(0xD800)]).is_none());
+    }
+}
@@ -0,0 +1,769 @@
+//! `StructNom` and `StructNomWrite` impls for std types.
+
+use std::convert::TryFrom;
+use std::io::{self, Write};
+use std::marker::PhantomData;
+
+use nom::number::complete as number;
+use nom::IResult;
+
+use crate::{Ctx, Endian, StructNom, StructNomCount, StructNomWrite, StructNomWriteCount};
+
+macro_rules! numeric_impl {
+    ($ty:ty, $le:ident, $be:ident) => {
+        impl<'a> StructNom<'a> for $ty {
+            fn nom_endian(input: &'a [u8], endian: Endian) -> IResult<&'a [u8], Self> {
+                match endian {
+                    Endian::Little => number::$le(input),
+                    Endian::Big => number::$be(input),
+                }
+            }
+        }
+
+        impl StructNomWrite for $ty {
+            fn write_endian<W: Write>(&self, out: &mut W, endian: Endian) -> io::Result<()> {
+                match endian {
+                    Endian::Little => out.write_all(&self.to_le_bytes()),
+                    Endian::Big => out.write_all(&self.to_be_bytes()),
+                }
+            }
+        }
+    };
+}
+
+numeric_impl!(u8, le_u8, be_u8);
+numeric_impl!(i8, le_i8, be_i8);
+numeric_impl!(u16, le_u16, be_u16);
+numeric_impl!(i16, le_i16, be_i16);
+numeric_impl!(u32, le_u32, be_u32);
+numeric_impl!(i32, le_i32, be_i32);
+numeric_impl!(u64, le_u64, be_u64);
+numeric_impl!(i64, le_i64, be_i64);
+numeric_impl!(u128, le_u128, be_u128);
+numeric_impl!(i128, le_i128, be_i128);
+numeric_impl!(f32, le_f32, be_f32);
+numeric_impl!(f64, le_f64, be_f64);
+
+/// A strict `bool`, any byte but `0` or `1` is an error. Fields that treat
+/// every non-zero byte as `true` use `#[snom(nonzero)]`.
+impl<'a> StructNom<'a> for bool {
+    fn nom_endian(input: &'a [u8], endian: Endian) -> IResult<&'a [u8], Self> {
+        match u8::nom_endian(input, endian)? {
+            (rest, 0) => Ok((rest, false)),
+            (rest, 1) => Ok((rest, true)),
+            _ => Err(nom::Err::Error(nom::error::make_error(
+                input,
+                nom::error::ErrorKind::Verify,
+            ))),
+        }
+    }
+}
+
+impl StructNomWrite for bool {
+    fn write_endian<W: Write>(&self, out: &mut W, endian: Endian) -> io::Result<()> {
+        u8::from(*self).write_endian(out, endian)
+    }
+}
+
+/// A `char` is stored as its `u32` code point, surrogates and values past
+/// `char::MAX` are an error.
+impl<'a> StructNom<'a> for char {
+    fn nom_endian(input: &'a [u8], endian: Endian) -> IResult<&'a [u8], Self> {
+        let (rest, code) = u32::nom_endian(input, endian)?;
+
+        match char::from_u32(code) {
+            Some(c) => Ok((rest, c)),
+            None => Err(nom::Err::Error(nom::error::make_error(
+                input,
+                nom::error::ErrorKind::Verify,
+            ))),
+        }
+    }
+}
+
+impl StructNomWrite for char {
+    fn write_endian<W: Write>(&self, out: &mut W, endian: Endian) -> io::Result<()> {
+        u32::from(*self).write_endian(out, endian)
+    }
+}
+
+impl<'a> StructNom<'a> for () {
+    fn nom_endian(input: &'a [u8], _: Endian) -> IResult<&'a [u8], Self> {
+        Ok((input, ()))
+    }
+}
+
+impl StructNomWrite for () {
+    fn write_endian<W: Write>(&self, _: &mut W, _: Endian) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a, T: ?Sized> StructNom<'a> for PhantomData<T> {
+    fn nom_endian(input: &'a [u8], _: Endian) -> IResult<&'a [u8], Self> {
+        Ok((input, PhantomData))
+    }
+}
+
+impl<T: ?Sized> StructNomWrite for PhantomData<T> {
+    fn write_endian<W: Write>(&self, _: &mut W, _: Endian) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Tuples parse their elements in order, all with the same `Endian`.
+macro_rules! tuple_impl {
+    ($($name:ident)+) => {
+        impl<'a, $($name: StructNom<'a>),+> StructNom<'a> for ($($name,)+) {
+            fn nom_endian(input: &'a [u8], endian: Endian) -> IResult<&'a [u8], Self> {
+                Self::nom_field(input, endian, &Ctx::new(endian))
+            }
+
+            #[allow(non_snake_case)]
+            fn nom_field(
+                input: &'a [u8],
+                endian: Endian,
+                runtime: &Ctx,
+            ) -> IResult<&'a [u8], Self> {
+                $(let (input, $name) = $name::nom_field(input, endian, runtime)?;)+
+
+                Ok((input, ($($name,)+)))
+            }
+        }
+
+        impl<$($name: StructNomWrite),+> StructNomWrite for ($($name,)+) {
+            fn write_endian<W: Write>(&self, out: &mut W, endian: Endian) -> io::Result<()> {
+                self.write_field(out, endian, &Ctx::new(endian))
+            }
+
+            #[allow(non_snake_case)]
+            fn write_field<W: Write>(
+                &self,
+                out: &mut W,
+                endian: Endian,
+                runtime: &Ctx,
+            ) -> io::Result<()> {
+                let ($($name,)+) = self;
+                $($name.write_field(out, endian, runtime)?;)+
+
+                Ok(())
+            }
+        }
+    };
+}
+
+tuple_impl!(A);
+tuple_impl!(A B);
+tuple_impl!(A B C);
+tuple_impl!(A B C D);
+tuple_impl!(A B C D E);
+tuple_impl!(A B C D E F);
+tuple_impl!(A B C D E F G);
+tuple_impl!(A B C D E F G H);
+tuple_impl!(A B C D E F G H I);
+tuple_impl!(A B C D E F G H I J);
+tuple_impl!(A B C D E F G H I J K);
+tuple_impl!(A B C D E F G H I J K L);
+
+impl<'a, T: StructNom<'a>> StructNom<'a> for Vec<T> {
+    fn nom_endian(input: &'a [u8], endian: Endian) -> IResult<&'a [u8], Self> {
+        Self::nom_field(input, endian, &Ctx::new(endian))
+    }
+
+    fn nom_field(input: &'a [u8], endian: Endian, runtime: &Ctx) -> IResult<&'a [u8], Self> {
+        let (input, length) = u8::nom_endian(input, endian)?;
+
+        Self::nom_count(input, length as usize, endian, runtime)
+    }
+}
+
+impl<T: StructNomWrite> StructNomWrite for Vec<T> {
+    fn write_endian<W: Write>(&self, out: &mut W, endian: Endian) -> io::Result<()> {
+        self.write_field(out, endian, &Ctx::new(endian))
+    }
+
+    fn write_field<W: Write>(&self, out: &mut W, endian: Endian, runtime: &Ctx) -> io::Result<()> {
+        if self.len() > u8::MAX as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Vec length does not fit in its u8 length prefix",
+            ));
+        }
+
+        (self.len() as u8).write_endian(out, endian)?;
+        self.write_count(out, endian, runtime)
+    }
+}
+
+impl<'a, T: StructNom<'a>> StructNomCount<'a> for Vec<T> {
+    type Item = T;
+
+    fn nom_count(
+        input: &'a [u8],
+        count: usize,
+        endian: Endian,
+        runtime: &Ctx,
+    ) -> IResult<&'a [u8], Self> {
+        nom::multi::count(|input| T::nom_field(input, endian, runtime), count)(input)
+    }
+
+    fn nom_rest(mut input: &'a [u8], endian: Endian, runtime: &Ctx) -> IResult<&'a [u8], Self> {
+        let mut items = Vec::new();
+
+        while !input.is_empty() {
+            let (rest, item) = T::nom_field(input, endian, runtime)?;
+
+            // An element that consumes nothing would repeat forever.
+            if rest.len() == input.len() {
+                return Err(nom::Err::Error(nom::error::make_error(
+                    input,
+                    nom::error::ErrorKind::Many0,
+                )));
+            }
+
+            items.push(item);
+            input = rest;
+        }
+
+        Ok((input, items))
+    }
+
+    fn nom_until(
+        mut input: &'a [u8],
+        terminator: &[u8],
+        keep: bool,
+        endian: Endian,
+        runtime: &Ctx,
+    ) -> IResult<&'a [u8], Self> {
+        let mut items = Vec::new();
+
+        loop {
+            if input.starts_with(terminator) {
+                let rest = if keep {
+                    input
+                } else {
+                    &input[terminator.len()..]
+                };
+
+                return Ok((rest, items));
+            }
+
+            let (rest, item) = T::nom_field(input, endian, runtime)?;
+
+            if rest.len() == input.len() {
+                return Err(nom::Err::Error(nom::error::make_error(
+                    input,
+                    nom::error::ErrorKind::TakeUntil,
+                )));
+            }
+
+            items.push(item);
+            input = rest;
+        }
+    }
+
+    fn nom_until_item<F: FnMut(&T) -> bool>(
+        mut input: &'a [u8],
+        mut is_end: F,
+        keep: bool,
+        endian: Endian,
+        runtime: &Ctx,
+    ) -> IResult<&'a [u8], Self> {
+        let mut items = Vec::new();
+
+        loop {
+            let (rest, item) = T::nom_field(input, endian, runtime)?;
+
+            if is_end(&item) {
+                if keep {
+                    items.push(item);
+                }
+
+                return Ok((rest, items));
+            }
+
+            if rest.len() == input.len() {
+                return Err(nom::Err::Error(nom::error::make_error(
+                    input,
+                    nom::error::ErrorKind::TakeUntil,
+                )));
+            }
+
+            items.push(item);
+            input = rest;
+        }
+    }
+}
+
+impl<T: StructNomWrite> StructNomWriteCount for Vec<T> {
+    fn count(&self) -> usize {
+        self.len()
+    }
+
+    fn write_count<W: Write>(&self, out: &mut W, endian: Endian, runtime: &Ctx) -> io::Result<()> {
+        for item in self {
+            item.write_field(out, endian, runtime)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks the bytes of a `String` are UTF-8, failing at `start` otherwise.
+fn utf8<'a>(start: &'a [u8], parsed: IResult<&'a [u8], Vec<u8>>) -> IResult<&'a [u8], String> {
+    let (input, bytes) = parsed?;
+
+    match String::from_utf8(bytes) {
+        Ok(text) => Ok((input, text)),
+        Err(_) => Err(nom::Err::Error(nom::error::make_error(
+            start,
+            nom::error::ErrorKind::Verify,
+        ))),
+    }
+}
+
+impl<'a> StructNom<'a> for String {
+    fn nom_endian(input: &'a [u8], endian: Endian) -> IResult<&'a [u8], Self> {
+        utf8(input, Vec::nom_endian(input, endian))
+    }
+}
+
+impl StructNomWrite for String {
+    fn write_endian<W: Write>(&self, out: &mut W, endian: Endian) -> io::Result<()> {
+        self.as_bytes().to_vec().write_endian(out, endian)
+    }
+}
+
+/// A `String` counts and terminates in UTF-8 bytes.
+impl<'a> StructNomCount<'a> for String {
+    type Item = u8;
+
+    fn nom_count(
+        input: &'a [u8],
+        count: usize,
+        endian: Endian,
+        runtime: &Ctx,
+    ) -> IResult<&'a [u8], Self> {
+        utf8(input, Vec::nom_count(input, count, endian, runtime))
+    }
+
+    fn nom_rest(input: &'a [u8], endian: Endian, runtime: &Ctx) -> IResult<&'a [u8], Self> {
+        utf8(input, Vec::nom_rest(input, endian, runtime))
+    }
+
+    fn nom_until(
+        input: &'a [u8],
+        terminator: &[u8],
+        keep: bool,
+        endian: Endian,
+        runtime: &Ctx,
+    ) -> IResult<&'a [u8], Self> {
+        utf8(
+            input,
+            Vec::nom_until(input, terminator, keep, endian, runtime),
+        )
+    }
+
+    fn nom_until_item<F: FnMut(&u8) -> bool>(
+        input: &'a [u8],
+        is_end: F,
+        keep: bool,
+        endian: Endian,
+        runtime: &Ctx,
+    ) -> IResult<&'a [u8], Self> {
+        utf8(
+            input,
+            Vec::nom_until_item(input, is_end, keep, endian, runtime),
+        )
+    }
+}
+
+impl StructNomWriteCount for String {
+    fn count(&self) -> usize {
+        self.len()
+    }
+
+    fn write_count<W: Write>(&self, out: &mut W, _: Endian, _: &Ctx) -> io::Result<()> {
+        out.write_all(self.as_bytes())
+    }
+}
+
+/// Arrays of any element type, `[u8; N]` fields of derived types copy their
+/// bytes in one go instead.
+impl<'a, T: StructNom<'a>, const N: usize> StructNom<'a> for [T; N] {
+    fn nom_endian(input: &'a [u8], endian: Endian) -> IResult<&'a [u8], Self> {
+        Self::nom_field(input, endian, &Ctx::new(endian))
+    }
+
+    fn nom_field(input: &'a [u8], endian: Endian, runtime: &Ctx) -> IResult<&'a [u8], Self> {
+        let (input, items) = Vec::nom_count(input, N, endian, runtime)?;
+
+        match <[T; N]>::try_from(items) {
+            Ok(array) => Ok((input, array)),
+            Err(_) => unreachable!("parsed a count of {} elements", N),
+        }
+    }
+}
+
+impl<T: StructNomWrite, const N: usize> StructNomWrite for [T; N] {
+    fn write_endian<W: Write>(&self, out: &mut W, endian: Endian) -> io::Result<()> {
+        self.write_field(out, endian, &Ctx::new(endian))
+    }
+
+    fn write_field<W: Write>(&self, out: &mut W, endian: Endian, runtime: &Ctx) -> io::Result<()> {
+        for item in self {
+            item.write_field(out, endian, runtime)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Fails with `TakeUntil` at `start` if `input` is out of bytes.
+fn until_error<T>(start: &[u8]) -> IResult<&[u8], T> {
+    Err(nom::Err::Error(nom::error::make_error(
+        start,
+        nom::error::ErrorKind::TakeUntil,
+    )))
+}
+
+/// Borrowed bytes, prefixed by a `u8` length like a `Vec<u8>`.
+impl<'a> StructNom<'a> for &'a [u8] {
+    fn nom_endian(input: &'a [u8], endian: Endian) -> IResult<&'a [u8], Self> {
+        let (input, length) = u8::nom_endian(input, endian)?;
+
+        Self::nom_count(input, length as usize, endian, &Ctx::new(endian))
+    }
+}
+
+impl StructNomWrite for &[u8] {
+    fn write_endian<W: Write>(&self, out: &mut W, endian: Endian) -> io::Result<()> {
+        if self.len() > u8::MAX as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "slice length does not fit in its u8 length prefix",
+            ));
+        }
+
+        (self.len() as u8).write_endian(out, endian)?;
+        self.write_count(out, endian, &Ctx::new(endian))
+    }
+}
+
+impl<'a> StructNomCount<'a> for &'a [u8] {
+    type Item = u8;
+
+    fn nom_count(input: &'a [u8], count: usize, _: Endian, _: &Ctx) -> IResult<&'a [u8], Self> {
+        nom::bytes::complete::take(count)(input)
+    }
+
+    fn nom_rest(input: &'a [u8], _: Endian, _: &Ctx) -> IResult<&'a [u8], Self> {
+        Ok((&input[input.len()..], input))
+    }
+
+    fn nom_until(
+        input: &'a [u8],
+        terminator: &[u8],
+        keep: bool,
+        _: Endian,
+        _: &Ctx,
+    ) -> IResult<&'a [u8], Self> {
+        let end = (0..=input.len()).find(|&end| input[end..].starts_with(terminator));
+
+        match end {
+            Some(end) if keep => Ok((&input[end..], &input[..end])),
+            Some(end) => Ok((&input[end + terminator.len()..], &input[..end])),
+            None => until_error(input),
+        }
+    }
+
+    fn nom_until_item<F: FnMut(&u8) -> bool>(
+        input: &'a [u8],
+        is_end: F,
+        keep: bool,
+        _: Endian,
+        _: &Ctx,
+    ) -> IResult<&'a [u8], Self> {
+        match input.iter().position(is_end) {
+            Some(end) if keep => Ok((&input[end + 1..], &input[..=end])),
+            Some(end) => Ok((&input[end + 1..], &input[..end])),
+            None => until_error(input),
+        }
+    }
+}
+
+impl StructNomWriteCount for &[u8] {
+    fn count(&self) -> usize {
+        self.len()
+    }
+
+    fn write_count<W: Write>(&self, out: &mut W, _: Endian, _: &Ctx) -> io::Result<()> {
+        out.write_all(self)
+    }
+}
+
+/// Checks borrowed bytes are UTF-8, failing at `start` otherwise.
+fn str_utf8<'a>(
+    start: &'a [u8],
+    parsed: IResult<&'a [u8], &'a [u8]>,
+) -> IResult<&'a [u8], &'a str> {
+    let (input, bytes) = parsed?;
+
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Ok((input, text)),
+        Err(_) => Err(nom::Err::Error(nom::error::make_error(
+            start,
+            nom::error::ErrorKind::Verify,
+        ))),
+    }
+}
+
+impl<'a> StructNom<'a> for &'a str {
+    fn nom_endian(input: &'a [u8], endian: Endian) -> IResult<&'a [u8], Self> {
+        str_utf8(input, <&[u8]>::nom_endian(input, endian))
+    }
+}
+
+impl StructNomWrite for &str {
+    fn write_endian<W: Write>(&self, out: &mut W, endian: Endian) -> io::Result<()> {
+        self.as_bytes().write_endian(out, endian)
+    }
+}
+
+/// A `&str` counts and terminates in UTF-8 bytes, like a `String`.
+impl<'a> StructNomCount<'a> for &'a str {
+    type Item = u8;
+
+    fn nom_count(
+        input: &'a [u8],
+        count: usize,
+        endian: Endian,
+        runtime: &Ctx,
+    ) -> IResult<&'a [u8], Self> {
+        str_utf8(input, <&[u8]>::nom_count(input, count, endian, runtime))
+    }
+
+    fn nom_rest(input: &'a [u8], endian: Endian, runtime: &Ctx) -> IResult<&'a [u8], Self> {
+        str_utf8(input, <&[u8]>::nom_rest(input, endian, runtime))
+    }
+
+    fn nom_until(
+        input: &'a [u8],
+        terminator: &[u8],
+        keep: bool,
+        endian: Endian,
+        runtime: &Ctx,
+    ) -> IResult<&'a [u8], Self> {
+        str_utf8(
+            input,
+            <&[u8]>::nom_until(input, terminator, keep, endian, runtime),
+        )
+    }
+
+    fn nom_until_item<F: FnMut(&u8) -> bool>(
+        input: &'a [u8],
+        is_end: F,
+        keep: bool,
+        endian: Endian,
+        runtime: &Ctx,
+    ) -> IResult<&'a [u8], Self> {
+        str_utf8(
+            input,
+            <&[u8]>::nom_until_item(input, is_end, keep, endian, runtime),
+        )
+    }
+}
+
+impl StructNomWriteCount for &str {
+    fn count(&self) -> usize {
+        self.len()
+    }
+
+    fn write_count<W: Write>(&self, out: &mut W, endian: Endian, runtime: &Ctx) -> io::Result<()> {
+        self.as_bytes().write_count(out, endian, runtime)
+    }
+}
+
+impl<'a, T: StructNom<'a>> StructNom<'a> for Option<T> {
+    fn nom_endian(input: &'a [u8], endian: Endian) -> IResult<&'a [u8], Self> {
+        Self::nom_field(input, endian, &Ctx::new(endian))
+    }
+
+    fn nom_field(input: &'a [u8], endian: Endian, runtime: &Ctx) -> IResult<&'a [u8], Self> {
+        nom::combinator::opt(|input| T::nom_field(input, endian, runtime))(input)
+    }
+}
+
+impl<T: StructNomWrite> StructNomWrite for Option<T> {
+    fn write_endian<W: Write>(&self, out: &mut W, endian: Endian) -> io::Result<()> {
+        self.write_field(out, endian, &Ctx::new(endian))
+    }
+
+    fn write_field<W: Write>(&self, out: &mut W, endian: Endian, runtime: &Ctx) -> io::Result<()> {
+        match self {
+            Some(value) => value.write_field(out, endian, runtime),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numeric_endian() {
+        let bytes = [0x12, 0x34];
+
+        assert_eq!(u16::nom_endian(&bytes, Endian::Little).unwrap().1, 0x3412);
+        assert_eq!(u16::nom_endian(&bytes, Endian::Big).unwrap().1, 0x1234);
+
+        let mut out = Vec::new();
+        0x1234u16.write_endian(&mut out, Endian::Big).unwrap();
+
+        assert_eq!(out, bytes);
+    }
+
+    #[test]
+    fn primitives() {
+        assert!(bool::nom(&[1]).unwrap().1);
+        assert!(bool::nom(&[2]).is_err());
+        assert_eq!(char::nom(&[0xE9, 0, 0, 0]).unwrap().1, 'é');
+        assert!(char::nom(&[0, 0xD8, 0, 0]).is_err());
+
+        let bytes = [&[0x12, 0x34][..], &[0; 14], &[0x56, 7]].concat();
+        let (rest, value) =
+            <(u8, u128, (), PhantomData<str>)>::nom_endian(&bytes, Endian::Big).unwrap();
+
+        assert_eq!(rest, [7]);
+        assert_eq!(
+            value,
+            (
+                0x12,
+                0x3400_0000_0000_0000_0000_0000_0000_0056,
+                (),
+                PhantomData
+            )
+        );
+
+        let mut out = Vec::new();
+        value.write_endian(&mut out, Endian::Big).unwrap();
+
+        assert_eq!(out, bytes[..17]);
+    }
+
+    #[test]
+    fn vec_roundtrip() {
+        let value: Vec<u32> = vec![1, 2, 3];
+
+        let mut out = Vec::new();
+        value.write_endian(&mut out, Endian::Big).unwrap();
+
+        assert_eq!(out[0], 3);
+        assert_eq!(Vec::<u32>::nom_endian(&out, Endian::Big).unwrap().1, value);
+    }
+
+    #[test]
+    fn vec_rest() {
+        let (rest, items) =
+            Vec::<u16>::nom_rest(&[1, 0, 2, 0], Endian::Little, &Ctx::default()).unwrap();
+
+        assert!(rest.is_empty());
+        assert_eq!(items, [1, 2]);
+
+        assert!(Vec::<u16>::nom_rest(&[1, 0, 2], Endian::Little, &Ctx::default()).is_err());
+
+        struct Empty;
+
+        impl<'a> StructNom<'a> for Empty {
+            fn nom_endian(input: &'a [u8], _: Endian) -> IResult<&'a [u8], Self> {
+                Ok((input, Empty))
+            }
+        }
+
+        assert!(Vec::<Empty>::nom_rest(&[1], Endian::Little, &Ctx::default()).is_err());
+    }
+
+    #[test]
+    fn vec_until() {
+        let bytes = [1, 2, 0, 3];
+
+        assert_eq!(
+            Vec::<u8>::nom_until(&bytes, &[0], false, Endian::Little, &Ctx::default()).unwrap(),
+            (&[3][..], vec![1, 2])
+        );
+        assert_eq!(
+            Vec::<u8>::nom_until(&bytes, &[0], true, Endian::Little, &Ctx::default()).unwrap(),
+            (&[0, 3][..], vec![1, 2])
+        );
+        assert!(
+            Vec::<u8>::nom_until(&bytes, &[9], false, Endian::Little, &Ctx::default()).is_err()
+        );
+
+        let is_end = |item: &u8| *item == 0;
+
+        assert_eq!(
+            Vec::<u8>::nom_until_item(&bytes, is_end, true, Endian::Little, &Ctx::default())
+                .unwrap(),
+            (&[3][..], vec![1, 2, 0])
+        );
+    }
+
+    #[test]
+    fn string_utf8() {
+        let (_, text) = String::nom(&[2, 0xC3, 0xA9]).unwrap();
+
+        assert_eq!(text, "é");
+        assert!(String::nom(&[1, 0xFF]).is_err());
+        assert_eq!(
+            String::nom_until(b"ab\0", &[0], false, Endian::Little, &Ctx::default())
+                .unwrap()
+                .1,
+            "ab"
+        );
+    }
+
+    #[test]
+    fn borrowed() {
+        let bytes = [2, b'h', b'i', b'!', 0, 7];
+
+        let (rest, text) = <&str>::nom(&bytes).unwrap();
+
+        assert_eq!((rest, text), (&bytes[3..], "hi"));
+        assert_eq!(
+            <&[u8]>::nom_until(rest, &[0], false, Endian::Little, &Ctx::default()).unwrap(),
+            (&[7][..], &b"!"[..])
+        );
+        assert_eq!(
+            <&[u8]>::nom_until(rest, &[0, 7], true, Endian::Little, &Ctx::default()).unwrap(),
+            (&[0, 7][..], &b"!"[..])
+        );
+        assert!(<&[u8]>::nom_until(rest, &[9], false, Endian::Little, &Ctx::default()).is_err());
+        assert!(<&str>::nom_count(&[0xFF], 1, Endian::Little, &Ctx::default()).is_err());
+
+        let mut out = Vec::new();
+        "hi".write(&mut out).unwrap();
+
+        assert_eq!(out, bytes[..3]);
+    }
+
+    #[test]
+    fn arrays() {
+        let (rest, regs) = <[u16; 2]>::nom_endian(&[0, 1, 0, 2, 3], Endian::Big).unwrap();
+
+        assert_eq!((rest, regs), (&[3][..], [1, 2]));
+        assert!(<[u16; 2]>::nom(&[0, 1, 0]).is_err());
+
+        let mut out = Vec::new();
+        regs.write_endian(&mut out, Endian::Big).unwrap();
+
+        assert_eq!(out, [0, 1, 0, 2]);
+    }
+
+    #[test]
+    fn vec_too_long() {
+        let value = vec![0u8; 256];
+
+        assert!(value.write(&mut Vec::new()).is_err());
+    }
+}
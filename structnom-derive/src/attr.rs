@@ -0,0 +1,1829 @@
+//! Parsing logic for StructNom attributes.
+
+use quote::{quote, ToTokens};
+use syn::parse::{Lookahead1, Parse, ParseBuffer, ParseStream};
+use syn::{parenthesized, punctuated::Punctuated, Attribute, LitInt, LitStr, Token};
+// use syn::lookahead::TokenMarker;
+
+use syn::Result as SynResult;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnomArg {
+    Match(MatchArg),
+    Parser(ValueArg),
+    Effect(EffectArg),
+    Write(WriteArg),
+    Config(ConfigArg),
+}
+
+impl SnomArg {
+    pub fn parse(attr: &Attribute) -> SynResult<Option<Self>> {
+        // println!("SnomArg parse: {:?}", attr);
+
+        if is_structnom_attr(attr) {
+            // println!("Is Snom Attr");
+
+            if attr.tts.is_empty() {
+                return Err(syn::Error::new_spanned(attr, "expected `#[snom(...)]`"));
+            }
+
+            let parsed = syn::parse2::<SnomArg>(attr.tts.clone());
+            // println!("Parsed Attr: {:?}", parsed);
+
+            Ok(Some(parsed?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn match_arg(&self) -> Option<&MatchArg> {
+        if let SnomArg::Match(ref a) = self {
+            Some(a)
+        } else {
+            None
+        }
+    }
+
+    pub fn value_arg(&self) -> Option<&ValueArg> {
+        if let SnomArg::Parser(ref a) = self {
+            Some(a)
+        } else {
+            None
+        }
+    }
+
+    pub fn effect_arg(&self) -> Option<&EffectArg> {
+        if let SnomArg::Effect(ref a) = self {
+            Some(a)
+        } else {
+            None
+        }
+    }
+
+    pub fn write_arg(&self) -> Option<&WriteArg> {
+        if let SnomArg::Write(ref a) = self {
+            Some(a)
+        } else {
+            None
+        }
+    }
+
+    pub fn config_arg(&self) -> Option<&ConfigArg> {
+        if let SnomArg::Config(ref a) = self {
+            Some(a)
+        } else {
+            None
+        }
+    }
+}
+
+/// Parses every `#[snom(...)]` attribute in `attrs`, skipping foreign ones.
+pub fn parse_args(attrs: &[Attribute]) -> SynResult<Vec<SnomArg>> {
+    let mut args = Vec::new();
+
+    for attr in attrs {
+        if let Some(arg) = SnomArg::parse(attr)? {
+            args.push(arg);
+        }
+    }
+
+    Ok(args)
+}
+
+/// Parses the `#[snom(...)]` attributes of a struct or enum. Unlike on a
+/// field, a `selector` there names a type.
+pub fn parse_type_args(attrs: &[Attribute]) -> SynResult<Vec<SnomArg>> {
+    let mut args = Vec::new();
+
+    for attr in attrs {
+        if is_structnom_attr(attr) && is_selector(attr) {
+            let selector = syn::parse2::<TypeSelector>(attr.tts.clone())?;
+            args.push(SnomArg::Config(selector.0));
+        } else if let Some(arg) = SnomArg::parse(attr)? {
+            args.push(arg);
+        }
+    }
+
+    Ok(args)
+}
+
+/// Whether `attr` is a `#[snom(selector ...)]`.
+fn is_selector(attr: &Attribute) -> bool {
+    use proc_macro2::TokenTree;
+
+    match attr.tts.clone().into_iter().next() {
+        Some(TokenTree::Group(group)) => matches!(
+            group.stream().into_iter().next(),
+            Some(TokenTree::Ident(ident)) if ident == "selector"
+        ),
+        _ => false,
+    }
+}
+
+struct TypeSelector(ConfigArg);
+
+impl Parse for TypeSelector {
+    fn parse(input: ParseStream) -> SynResult<Self> {
+        let input = pop_parens(input)?;
+
+        Ok(TypeSelector(ConfigArg::SelectorType {
+            selector_token: input.parse()?,
+            eq_token: input.parse()?,
+            value: input.parse()?,
+        }))
+    }
+}
+
+impl Parse for SnomArg {
+    fn parse(input: ParseStream) -> SynResult<Self> {
+        // println!("SnomArg Input: {:#?}", input);
+        // We're looking at the tts of a snom attribute:
+        //
+        // #[snom(some_tts)]
+        //       ^^^^^^^^^^
+        //
+        // This inclues the surrounding parentheses.
+        // Therefore we first remove them using the
+        // parenthesized! macro.
+        // let content;
+        // parenthesized!(content in input);
+        let input = pop_parens(input)?;
+
+        // println!("SnomArg Input: {:?}", input);
+
+        let lookahead = input.lookahead1();
+
+        // println!("SnomArg: input {:#?}", input);
+
+        if looking_at_match(&lookahead) {
+            // println!("Looking At: {}", "match");
+
+            Ok(SnomArg::Match(input.parse()?))
+        } else if looking_at_parser(&lookahead) {
+            // println!("Looking At: {}", "parser");
+
+            Ok(SnomArg::Parser(input.parse()?))
+        } else if looking_at_effect(&lookahead) {
+            // println!("Looking At: {}", "effect");
+
+            Ok(SnomArg::Effect(input.parse()?))
+        } else if lookahead.peek(kw::writer) {
+            // println!("Looking At: {}", "writer");
+
+            Ok(SnomArg::Write(input.parse()?))
+        } else if looking_at_config(&lookahead) {
+            // println!("Looking At: {}", "config");
+
+            Ok(SnomArg::Config(input.parse()?))
+        } else if input.is_empty() {
+            Err(input.error("expected a snom argument"))
+        } else {
+            let token: proc_macro2::TokenTree = input.parse()?;
+
+            Err(syn::Error::new(
+                token.span(),
+                format!("unknown snom argument `{}`", token),
+            ))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MatchArg {
+    Range(RangeArg),
+    Val {
+        val_token: kw::val,
+        eq_token: Token![=],
+        value: LitInt,
+    },
+    Values {
+        values_token: kw::values,
+        paren_token: syn::token::Paren,
+        values: Punctuated<LitInt, Token![,]>,
+    },
+    /// Matches every discriminant in one of `ranges`.
+    Ranges {
+        range_token: kw::range,
+        eq_token: Token![=],
+        ranges: Punctuated<IntRange, Token![,]>,
+    },
+    /// Matches input starting with `value`, for enums tagged by a byte
+    /// string rather than an integer.
+    Magic {
+        magic_token: kw::magic,
+        eq_token: Token![=],
+        value: Bytes,
+    },
+    /// Matches every discriminant no other variant claims, the variant's
+    /// first field receives it.
+    Default {
+        default_token: Token![default],
+    },
+}
+
+impl Parse for MatchArg {
+    fn parse(input: ParseStream) -> SynResult<Self> {
+        // println!("MatchArg Input: {:#?}", input);
+
+        let lookahead = input.lookahead1();
+
+        if lookahead.peek(kw::range) {
+            let range_token = input.parse::<kw::range>()?;
+
+            if input.peek(Token![=]) {
+                let eq_token = input.parse()?;
+                let ranges = input.parse_terminated(IntRange::parse)?;
+
+                if ranges.is_empty() {
+                    return Err(syn::Error::new(
+                        range_token.span,
+                        "`range` needs at least one range of discriminants",
+                    ));
+                }
+
+                return Ok(MatchArg::Ranges {
+                    range_token,
+                    eq_token,
+                    ranges,
+                });
+            }
+
+            let input = pop_parens(input)?;
+            Ok(MatchArg::Range(input.parse()?))
+        } else if lookahead.peek(kw::val) {
+            Ok(MatchArg::Val {
+                val_token: input.parse()?,
+                eq_token: input.parse()?,
+                value: input.parse()?,
+            })
+        } else if lookahead.peek(kw::magic) {
+            Ok(MatchArg::Magic {
+                magic_token: input.parse()?,
+                eq_token: input.parse()?,
+                value: input.parse()?,
+            })
+        } else if lookahead.peek(Token![default]) {
+            Ok(MatchArg::Default {
+                default_token: input.parse()?,
+            })
+        } else if lookahead.peek(kw::values) {
+            let content;
+            let values_token: kw::values = input.parse()?;
+            let paren_token = parenthesized!(content in input);
+            let values = content.parse_terminated(LitInt::parse)?;
+
+            if values.is_empty() {
+                return Err(syn::Error::new(
+                    values_token.span,
+                    "`values` needs at least one discriminant",
+                ));
+            }
+
+            Ok(MatchArg::Values {
+                values_token,
+                paren_token,
+                values,
+            })
+        } else {
+            Err(lookahead.error())
+        }
+    }
+}
+
+impl MatchArg {
+    pub fn span(&self) -> proc_macro2::Span {
+        match self {
+            MatchArg::Range(range) => range.span(),
+            MatchArg::Val { val_token, .. } => val_token.span,
+            MatchArg::Values { values_token, .. } => values_token.span,
+            MatchArg::Ranges { range_token, .. } => range_token.span,
+            MatchArg::Magic { magic_token, .. } => magic_token.span,
+            MatchArg::Default { default_token } => default_token.span,
+        }
+    }
+}
+
+/// A range of discriminants, `0x10..=0x1F` or `0x10..0x20`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntRange {
+    pub start: LitInt,
+    pub limits: syn::RangeLimits,
+    pub end: LitInt,
+}
+
+impl Parse for IntRange {
+    fn parse(input: ParseStream) -> SynResult<Self> {
+        Ok(IntRange {
+            start: input.parse()?,
+            limits: input.parse()?,
+            end: input.parse()?,
+        })
+    }
+}
+
+impl ToTokens for IntRange {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        self.start.to_tokens(tokens);
+        match self.limits {
+            syn::RangeLimits::HalfOpen(ref dots) => dots.to_tokens(tokens),
+            syn::RangeLimits::Closed(ref dots) => dots.to_tokens(tokens),
+        }
+        self.end.to_tokens(tokens);
+    }
+}
+
+impl IntRange {
+    /// The first and last value in the range, `None` if it is empty.
+    pub fn bounds(&self) -> Option<(u64, u64)> {
+        let (start, end) = (self.start.value(), self.end.value());
+
+        match self.limits {
+            syn::RangeLimits::Closed(_) if start <= end => Some((start, end)),
+            syn::RangeLimits::HalfOpen(_) if start < end => Some((start, end - 1)),
+            _ => None,
+        }
+    }
+
+    pub fn span(&self) -> proc_macro2::Span {
+        self.start.span()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RangeArg {
+    Start {
+        start_token: kw::start,
+        eq_token: Token![=],
+        value: LitInt,
+    },
+    Skip {
+        skip_token: kw::skip,
+        eq_token: Option<Token![=]>,
+        value: Option<LitInt>,
+    },
+    End {
+        end_token: kw::end,
+        eq_token: Token![=],
+        value: LitInt,
+    },
+}
+
+impl Parse for RangeArg {
+    fn parse(input: ParseStream) -> SynResult<Self> {
+        // println!("RangeArg Input: {:#?}", input);
+
+        let lookahead = input.lookahead1();
+
+        if lookahead.peek(kw::start) {
+            Ok(RangeArg::Start {
+                start_token: input.parse()?,
+                eq_token: input.parse()?,
+                value: input.parse()?,
+            })
+        } else if lookahead.peek(kw::skip) {
+            Ok(RangeArg::Skip {
+                skip_token: input.parse()?,
+                eq_token: input.parse()?,
+                value: input.parse()?,
+            })
+        } else if lookahead.peek(kw::end) {
+            Ok(RangeArg::End {
+                end_token: input.parse()?,
+                eq_token: input.parse()?,
+                value: input.parse()?,
+            })
+        } else {
+            Err(lookahead.error())
+        }
+    }
+}
+
+impl RangeArg {
+    pub fn span(&self) -> proc_macro2::Span {
+        match self {
+            RangeArg::Start { start_token, .. } => start_token.span,
+            RangeArg::Skip { skip_token, .. } => skip_token.span,
+            RangeArg::End { end_token, .. } => end_token.span,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueArg {
+    Parser {
+        parser_token: kw::parser,
+        eq_token: Token![=],
+        value: syn::Path,
+    },
+    Bits {
+        bits_token: kw::bits,
+        parens_token: syn::token::Paren,
+        count: LitInt,
+    },
+    TagBits {
+        bits_token: kw::bits,
+        parens_token: syn::token::Paren,
+        count: LitInt,
+        comma_token: Token![,],
+        pattern: syn::Pat,
+    },
+    Skip {
+        skip_token: kw::skip,
+    },
+    Iter {
+        iter_token: kw::iter,
+    },
+    Switch {
+        switch_token: kw::switch,
+        eq_token: Token![=],
+        value: syn::Path,
+    },
+    Until {
+        until_token: kw::until,
+        eq_token: Token![=],
+        value: Terminator,
+    },
+}
+
+impl Parse for ValueArg {
+    fn parse(input: ParseStream) -> SynResult<Self> {
+        let lookahead = input.lookahead1();
+
+        // println!("ValueArg Input: {:?}", input);
+
+        if lookahead.peek(kw::parser) {
+            Ok(ValueArg::Parser {
+                parser_token: input.parse()?,
+                eq_token: input.parse()?,
+                value: parse_path(input)?,
+            })
+        } else if lookahead.peek(kw::skip) {
+            Ok(ValueArg::Skip {
+                skip_token: input.parse()?,
+            })
+        } else if lookahead.peek(kw::iter) {
+            Ok(ValueArg::Iter {
+                iter_token: input.parse()?,
+            })
+        } else if lookahead.peek(kw::switch) {
+            Ok(ValueArg::Switch {
+                switch_token: input.parse()?,
+                eq_token: input.parse()?,
+                value: input.parse()?,
+            })
+        } else if lookahead.peek(kw::until) {
+            Ok(ValueArg::Until {
+                until_token: input.parse()?,
+                eq_token: input.parse()?,
+                value: input.parse()?,
+            })
+        } else if lookahead.peek(kw::bits) {
+            let content;
+
+            let bits_token: kw::bits = input.parse()?;
+            let parens_token: syn::token::Paren = parenthesized!(content in input);
+            let count: LitInt = content.parse()?;
+
+            if count.value() > 128 {
+                return Err(syn::Error::new(
+                    count.span(),
+                    "`bits` reads at most 128 bits",
+                ));
+            }
+
+            // println!("*** Lookahead ***", content.look)
+
+            if content.peek(Token![,]) {
+                let comma_token: Token![,] = content.parse()?;
+                let pattern: syn::Pat = content.parse()?;
+
+                let val = Ok(ValueArg::TagBits {
+                    bits_token,
+                    parens_token,
+                    count,
+                    comma_token,
+                    pattern,
+                });
+
+                // println!("TagBits: {:?}", val);
+
+                val
+            } else {
+                let val = Ok(ValueArg::Bits {
+                    bits_token,
+                    parens_token,
+                    count,
+                });
+
+                // println!("=== Bits ===: {:?}", val);
+
+                val
+            }
+        } else {
+            Err(lookahead.error())
+        }
+    }
+}
+
+impl ValueArg {
+    pub fn span(&self) -> proc_macro2::Span {
+        match self {
+            ValueArg::Parser { parser_token, .. } => parser_token.span,
+            ValueArg::Bits { bits_token, .. } | ValueArg::TagBits { bits_token, .. } => {
+                bits_token.span
+            }
+            ValueArg::Skip { skip_token } => skip_token.span,
+            ValueArg::Iter { iter_token } => iter_token.span,
+            ValueArg::Switch { switch_token, .. } => switch_token.span,
+            ValueArg::Until { until_token, .. } => until_token.span,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EffectArg {
+    Tag {
+        tag_token: kw::tag,
+        paren_token: syn::token::Paren,
+        value: TagEither,
+    },
+    Take {
+        take_token: kw::take,
+        paren_token: syn::token::Paren,
+        value: LitInt,
+    },
+    Debug {
+        debug_token: kw::debug,
+        eq_token: Option<Token![=]>,
+        value: Option<LitStr>,
+    },
+    Call {
+        call_token: kw::call,
+        paren_token: syn::token::Paren,
+        value: syn::Path,
+    },
+}
+
+impl Parse for EffectArg {
+    fn parse(input: ParseStream) -> SynResult<Self> {
+        let lookahead = input.lookahead1();
+
+        // println!("+++ EffectArg Input: {}", input);
+
+        if lookahead.peek(kw::tag) {
+            let content;
+            Ok(EffectArg::Tag {
+                tag_token: input.parse()?,
+                paren_token: parenthesized!(content in input),
+                value: content.parse()?,
+            })
+        } else if lookahead.peek(kw::debug) {
+            Ok(EffectArg::Debug {
+                debug_token: input.parse()?,
+                eq_token: input.parse()?,
+                value: input.parse()?,
+            })
+        } else if lookahead.peek(kw::call) {
+            let content;
+            Ok(EffectArg::Call {
+                call_token: input.parse()?,
+                paren_token: parenthesized!(content in input),
+                value: content.parse()?,
+            })
+        } else if lookahead.peek(kw::take) {
+            let content;
+            Ok(EffectArg::Take {
+                take_token: input.parse()?,
+                paren_token: parenthesized!(content in input),
+                value: content.parse()?,
+            })
+        } else {
+            Err(lookahead.error())
+        }
+    }
+}
+
+impl EffectArg {
+    /// The parser whose output gets thrown away before the field is parsed.
+    pub fn parser(&self) -> SynResult<proc_macro2::TokenStream> {
+        match self {
+            EffectArg::Tag { value, .. } => match value {
+                TagEither::Slice(ident) => {
+                    Ok(quote! { ::structnom::nom::bytes::complete::tag(#ident) })
+                }
+                TagEither::Values(vals) => {
+                    Ok(quote! { ::structnom::nom::bytes::complete::tag(&[#(#vals),*][..]) })
+                }
+            },
+            EffectArg::Debug { debug_token, .. } => Err(syn::Error::new(
+                debug_token.span,
+                "`debug` is not implemented yet",
+            )),
+            EffectArg::Call { value, .. } => Ok(quote! { #value }),
+            EffectArg::Take { value, .. } => {
+                let value = usize_lit(value);
+                Ok(quote!(::structnom::nom::bytes::complete::take(#value)))
+            }
+        }
+    }
+
+    pub fn span(&self) -> proc_macro2::Span {
+        match self {
+            EffectArg::Tag { tag_token, .. } => tag_token.span,
+            EffectArg::Take { take_token, .. } => take_token.span,
+            EffectArg::Debug { debug_token, .. } => debug_token.span,
+            EffectArg::Call { call_token, .. } => call_token.span,
+        }
+    }
+
+    /// The statements that write back whatever this effect consumed, an
+    /// error if the consumed bytes cannot be reconstructed.
+    pub fn writer(&self) -> SynResult<proc_macro2::TokenStream> {
+        match self {
+            EffectArg::Tag { value, .. } => match value {
+                TagEither::Slice(ident) => Ok(quote! { __snom_out.write_all(#ident)?; }),
+                TagEither::Values(vals) => Ok(quote! { __snom_out.write_all(&[#(#vals),*])?; }),
+            },
+            EffectArg::Take { value, .. } => Ok(quote! { __snom_out.write_all(&[0u8; #value])?; }),
+            EffectArg::Debug { .. } => Ok(quote! {}),
+            EffectArg::Call { call_token, .. } => Err(syn::Error::new(
+                call_token.span,
+                "`call` cannot be written back, the bytes it consumes are not kept",
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WriteArg {
+    Writer {
+        writer_token: kw::writer,
+        eq_token: Token![=],
+        value: syn::Path,
+    },
+}
+
+impl Parse for WriteArg {
+    fn parse(input: ParseStream) -> SynResult<Self> {
+        let lookahead = input.lookahead1();
+
+        if lookahead.peek(kw::writer) {
+            Ok(WriteArg::Writer {
+                writer_token: input.parse()?,
+                eq_token: input.parse()?,
+                value: input.parse()?,
+            })
+        } else {
+            Err(lookahead.error())
+        }
+    }
+}
+
+/// Arguments that configure how a type or field is parsed without being a
+/// parser of their own.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigArg {
+    Endian {
+        endian_token: kw::endian,
+        eq_token: Token![=],
+        value: Endian,
+    },
+    Length {
+        length_token: kw::length,
+        eq_token: Token![=],
+        value: syn::Type,
+    },
+    LengthParser {
+        length_parser_token: kw::length_parser,
+        eq_token: Token![=],
+        value: syn::Path,
+    },
+    /// Writes the length prefix a `length_parser` reads.
+    LengthWriter {
+        length_writer_token: kw::length_writer,
+        eq_token: Token![=],
+        value: syn::Path,
+    },
+    Count {
+        count_token: kw::count,
+        eq_token: Token![=],
+        value: syn::Expr,
+    },
+    Size {
+        size_token: kw::size,
+        eq_token: Token![=],
+        value: syn::Expr,
+    },
+    Complete {
+        complete_token: kw::complete,
+    },
+    KeepTerminator {
+        keep_terminator_token: kw::keep_terminator,
+    },
+    Nonzero {
+        nonzero_token: kw::nonzero,
+    },
+    /// Binds the discriminant that selected an enum variant to this field.
+    Discriminant {
+        discriminant_token: kw::discriminant,
+    },
+    Ctx {
+        ctx_token: kw::ctx,
+        eq_token: Token![=],
+        value: syn::Type,
+    },
+    WithCtx {
+        with_ctx_token: kw::with_ctx,
+        eq_token: Token![=],
+        value: syn::Expr,
+    },
+    Selector {
+        selector_token: kw::selector,
+        eq_token: Token![=],
+        value: syn::Expr,
+    },
+    /// The `selector` of an enum, the type of the discriminant its parent
+    /// passes in rather than a value.
+    SelectorType {
+        selector_token: kw::selector,
+        eq_token: Token![=],
+        value: syn::Type,
+    },
+    Cond {
+        cond_token: kw::cond,
+        eq_token: Token![=],
+        value: syn::Expr,
+    },
+    Padding {
+        padding_token: kw::padding,
+        eq_token: Token![=],
+        value: Bytes,
+    },
+    Encoding {
+        encoding_token: kw::encoding,
+        eq_token: Token![=],
+        value: Encoding,
+    },
+}
+
+impl Parse for ConfigArg {
+    fn parse(input: ParseStream) -> SynResult<Self> {
+        let lookahead = input.lookahead1();
+
+        if lookahead.peek(kw::endian) {
+            Ok(ConfigArg::Endian {
+                endian_token: input.parse()?,
+                eq_token: input.parse()?,
+                value: input.parse()?,
+            })
+        } else if lookahead.peek(kw::length) {
+            Ok(ConfigArg::Length {
+                length_token: input.parse()?,
+                eq_token: input.parse()?,
+                value: input.parse()?,
+            })
+        } else if lookahead.peek(kw::length_parser) {
+            Ok(ConfigArg::LengthParser {
+                length_parser_token: input.parse()?,
+                eq_token: input.parse()?,
+                value: parse_path(input)?,
+            })
+        } else if lookahead.peek(kw::length_writer) {
+            Ok(ConfigArg::LengthWriter {
+                length_writer_token: input.parse()?,
+                eq_token: input.parse()?,
+                value: parse_path(input)?,
+            })
+        } else if lookahead.peek(kw::count) {
+            Ok(ConfigArg::Count {
+                count_token: input.parse()?,
+                eq_token: input.parse()?,
+                value: input.parse()?,
+            })
+        } else if lookahead.peek(kw::size) {
+            Ok(ConfigArg::Size {
+                size_token: input.parse()?,
+                eq_token: input.parse()?,
+                value: input.parse()?,
+            })
+        } else if lookahead.peek(kw::complete) {
+            Ok(ConfigArg::Complete {
+                complete_token: input.parse()?,
+            })
+        } else if lookahead.peek(kw::keep_terminator) {
+            Ok(ConfigArg::KeepTerminator {
+                keep_terminator_token: input.parse()?,
+            })
+        } else if lookahead.peek(kw::nonzero) {
+            Ok(ConfigArg::Nonzero {
+                nonzero_token: input.parse()?,
+            })
+        } else if lookahead.peek(kw::discriminant) {
+            Ok(ConfigArg::Discriminant {
+                discriminant_token: input.parse()?,
+            })
+        } else if lookahead.peek(kw::ctx) {
+            Ok(ConfigArg::Ctx {
+                ctx_token: input.parse()?,
+                eq_token: input.parse()?,
+                value: input.parse()?,
+            })
+        } else if lookahead.peek(kw::with_ctx) {
+            Ok(ConfigArg::WithCtx {
+                with_ctx_token: input.parse()?,
+                eq_token: input.parse()?,
+                value: input.parse()?,
+            })
+        } else if lookahead.peek(kw::selector) {
+            Ok(ConfigArg::Selector {
+                selector_token: input.parse()?,
+                eq_token: input.parse()?,
+                value: input.parse()?,
+            })
+        } else if lookahead.peek(kw::cond) {
+            Ok(ConfigArg::Cond {
+                cond_token: input.parse()?,
+                eq_token: input.parse()?,
+                value: input.parse()?,
+            })
+        } else if lookahead.peek(kw::padding) {
+            Ok(ConfigArg::Padding {
+                padding_token: input.parse()?,
+                eq_token: input.parse()?,
+                value: input.parse()?,
+            })
+        } else if lookahead.peek(kw::encoding) {
+            Ok(ConfigArg::Encoding {
+                encoding_token: input.parse()?,
+                eq_token: input.parse()?,
+                value: input.parse()?,
+            })
+        } else {
+            Err(lookahead.error())
+        }
+    }
+}
+
+impl ConfigArg {
+    pub fn span(&self) -> proc_macro2::Span {
+        match self {
+            ConfigArg::Endian { endian_token, .. } => endian_token.span,
+            ConfigArg::Length { length_token, .. } => length_token.span,
+            ConfigArg::LengthParser {
+                length_parser_token,
+                ..
+            } => length_parser_token.span,
+            ConfigArg::LengthWriter {
+                length_writer_token,
+                ..
+            } => length_writer_token.span,
+            ConfigArg::Count { count_token, .. } => count_token.span,
+            ConfigArg::Size { size_token, .. } => size_token.span,
+            ConfigArg::Complete { complete_token } => complete_token.span,
+            ConfigArg::KeepTerminator {
+                keep_terminator_token,
+            } => keep_terminator_token.span,
+            ConfigArg::Nonzero { nonzero_token } => nonzero_token.span,
+            ConfigArg::Discriminant { discriminant_token } => discriminant_token.span,
+            ConfigArg::Ctx { ctx_token, .. } => ctx_token.span,
+            ConfigArg::WithCtx { with_ctx_token, .. } => with_ctx_token.span,
+            ConfigArg::Selector { selector_token, .. }
+            | ConfigArg::SelectorType { selector_token, .. } => selector_token.span,
+            ConfigArg::Cond { cond_token, .. } => cond_token.span,
+            ConfigArg::Padding { padding_token, .. } => padding_token.span,
+            ConfigArg::Encoding { encoding_token, .. } => encoding_token.span,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Endian {
+    Little,
+    Big,
+    Native,
+    /// The order the caller parses with, even inside a type that picked
+    /// its own.
+    Ctx,
+}
+
+impl Parse for Endian {
+    /// Accepts `big` as well as `"big"`.
+    fn parse(input: ParseStream) -> SynResult<Self> {
+        let lookahead = input.lookahead1();
+
+        let (name, span) = if lookahead.peek(syn::Ident) {
+            let ident = input.parse::<syn::Ident>()?;
+            (ident.to_string(), ident.span())
+        } else if lookahead.peek(LitStr) {
+            let lit = input.parse::<LitStr>()?;
+            (lit.value(), lit.span())
+        } else {
+            return Err(lookahead.error());
+        };
+
+        match name.to_ascii_lowercase().as_ref() {
+            "big" => Ok(Endian::Big),
+            "little" => Ok(Endian::Little),
+            "native" => Ok(Endian::Native),
+            "ctx" => Ok(Endian::Ctx),
+            _ => Err(syn::Error::new(
+                span,
+                format!(
+                    "unknown endianness `{}`, expected `big`, `little`, `native` or `ctx`",
+                    name
+                ),
+            )),
+        }
+    }
+}
+
+impl ToTokens for Endian {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let repr = match self {
+            Endian::Little => quote! { ::structnom::Endian::Little },
+            Endian::Big => quote! { ::structnom::Endian::Big },
+            Endian::Native => quote! { ::structnom::Endian::NATIVE },
+            Endian::Ctx => quote! { __snom_runtime.endian },
+        };
+
+        tokens.extend(repr);
+    }
+}
+
+/// The text encoding of a `String` field.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Encoding {
+    Utf8,
+    Ascii,
+    Latin1,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl Parse for Encoding {
+    /// Accepts `latin1` as well as `"ISO-8859-1"`, case and punctuation
+    /// are ignored.
+    fn parse(input: ParseStream) -> SynResult<Self> {
+        let lookahead = input.lookahead1();
+
+        let (name, span) = if lookahead.peek(syn::Ident) {
+            let ident = input.parse::<syn::Ident>()?;
+            (ident.to_string(), ident.span())
+        } else if lookahead.peek(LitStr) {
+            let lit = input.parse::<LitStr>()?;
+            (lit.value(), lit.span())
+        } else {
+            return Err(lookahead.error());
+        };
+
+        let kind: String = name
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+
+        match kind.as_ref() {
+            "utf8" => Ok(Encoding::Utf8),
+            "ascii" => Ok(Encoding::Ascii),
+            "latin1" | "iso88591" => Ok(Encoding::Latin1),
+            "utf16le" => Ok(Encoding::Utf16Le),
+            "utf16be" => Ok(Encoding::Utf16Be),
+            _ => Err(syn::Error::new(
+                span,
+                format!(
+                    "unknown encoding `{}`, expected `utf8`, `ascii`, `latin1`, `utf16le` or `utf16be`",
+                    name
+                ),
+            )),
+        }
+    }
+}
+
+impl Encoding {
+    /// The number of bytes in one code unit.
+    pub fn unit_size(self) -> usize {
+        match self {
+            Encoding::Utf8 | Encoding::Ascii | Encoding::Latin1 => 1,
+            Encoding::Utf16Le | Encoding::Utf16Be => 2,
+        }
+    }
+}
+
+impl ToTokens for Encoding {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let repr = match self {
+            Encoding::Utf8 => quote! { ::structnom::encoding::Utf8 },
+            Encoding::Ascii => quote! { ::structnom::encoding::Ascii },
+            Encoding::Latin1 => quote! { ::structnom::encoding::Latin1 },
+            Encoding::Utf16Le => quote! { ::structnom::encoding::Utf16Le },
+            Encoding::Utf16Be => quote! { ::structnom::encoding::Utf16Be },
+        };
+
+        tokens.extend(repr);
+    }
+}
+
+/// The byte order picked by the `endian` argument in `args`, if any.
+pub fn find_endian(args: &[SnomArg]) -> Option<Endian> {
+    args.iter()
+        .filter_map(SnomArg::config_arg)
+        .find_map(|arg| match arg {
+            ConfigArg::Endian { value, .. } => Some(*value),
+            _ => None,
+        })
+}
+
+/// The `length`, `length_parser` or `count` argument in `args`, if any.
+pub fn find_length(args: &[SnomArg]) -> Option<&ConfigArg> {
+    args.iter().filter_map(SnomArg::config_arg).find(|arg| {
+        matches!(
+            arg,
+            ConfigArg::Length { .. } | ConfigArg::LengthParser { .. } | ConfigArg::Count { .. }
+        )
+    })
+}
+
+/// The writer of a `length_parser` prefix, from `length_writer` in `args`.
+pub fn find_length_writer(args: &[SnomArg]) -> Option<&syn::Path> {
+    args.iter()
+        .filter_map(SnomArg::config_arg)
+        .find_map(|arg| match arg {
+            ConfigArg::LengthWriter { value, .. } => Some(value),
+            _ => None,
+        })
+}
+
+/// The `size` argument in `args`, if any.
+pub fn find_size(args: &[SnomArg]) -> Option<&ConfigArg> {
+    args.iter()
+        .filter_map(SnomArg::config_arg)
+        .find(|arg| matches!(arg, ConfigArg::Size { .. }))
+}
+
+/// The `complete` argument in `args`, if any.
+pub fn find_complete(args: &[SnomArg]) -> Option<&ConfigArg> {
+    args.iter()
+        .filter_map(SnomArg::config_arg)
+        .find(|arg| matches!(arg, ConfigArg::Complete { .. }))
+}
+
+/// The `nonzero` argument in `args`, if any.
+pub fn find_nonzero(args: &[SnomArg]) -> Option<&ConfigArg> {
+    args.iter()
+        .filter_map(SnomArg::config_arg)
+        .find(|arg| matches!(arg, ConfigArg::Nonzero { .. }))
+}
+
+/// The `discriminant` argument in `args`, if any.
+pub fn find_discriminant(args: &[SnomArg]) -> Option<&ConfigArg> {
+    args.iter()
+        .filter_map(SnomArg::config_arg)
+        .find(|arg| matches!(arg, ConfigArg::Discriminant { .. }))
+}
+
+/// The type of the context a type's parser takes, from `ctx` in `args`.
+pub fn find_ctx(args: &[SnomArg]) -> Option<&syn::Type> {
+    args.iter()
+        .filter_map(SnomArg::config_arg)
+        .find_map(|arg| match arg {
+            ConfigArg::Ctx { value, .. } => Some(value),
+            _ => None,
+        })
+}
+
+/// The context a field passes to its parser, from `with_ctx` in `args`. A
+/// field's `selector` is the context of an enum that takes one.
+pub fn find_with_ctx(args: &[SnomArg]) -> Option<&syn::Expr> {
+    args.iter()
+        .filter_map(SnomArg::config_arg)
+        .find_map(|arg| match arg {
+            ConfigArg::WithCtx { value, .. } | ConfigArg::Selector { value, .. } => Some(value),
+            _ => None,
+        })
+}
+
+/// The `selector` argument in `args`, if any.
+pub fn find_selector(args: &[SnomArg]) -> Option<&ConfigArg> {
+    args.iter().filter_map(SnomArg::config_arg).find(|arg| {
+        matches!(
+            arg,
+            ConfigArg::Selector { .. } | ConfigArg::SelectorType { .. }
+        )
+    })
+}
+
+/// The condition an `Option` field is present under, from `cond` in `args`.
+pub fn find_cond(args: &[SnomArg]) -> Option<&syn::Expr> {
+    args.iter()
+        .filter_map(SnomArg::config_arg)
+        .find_map(|arg| match arg {
+            ConfigArg::Cond { value, .. } => Some(value),
+            _ => None,
+        })
+}
+
+/// The bytes a `padding` argument in `args` pads with, if any.
+pub fn find_padding(args: &[SnomArg]) -> Option<&ConfigArg> {
+    args.iter()
+        .filter_map(SnomArg::config_arg)
+        .find(|arg| matches!(arg, ConfigArg::Padding { .. }))
+}
+
+/// The encoding picked by the `encoding` argument in `args`, if any.
+pub fn find_encoding(args: &[SnomArg]) -> Option<Encoding> {
+    args.iter()
+        .filter_map(SnomArg::config_arg)
+        .find_map(|arg| match arg {
+            ConfigArg::Encoding { value, .. } => Some(*value),
+            _ => None,
+        })
+}
+
+/// The `keep_terminator` argument in `args`, if any.
+pub fn find_keep_terminator(args: &[SnomArg]) -> Option<&ConfigArg> {
+    args.iter()
+        .filter_map(SnomArg::config_arg)
+        .find(|arg| matches!(arg, ConfigArg::KeepTerminator { .. }))
+}
+
+pub fn is_structnom_attr(attr: &syn::Attribute) -> bool {
+    attr.path.segments.len() == 1 && attr.path.segments[0].ident == "snom"
+}
+
+/// The integer type of a `#[repr(u8)]` among `attrs`, if any.
+pub fn find_repr(attrs: &[Attribute]) -> Option<syn::Ident> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("repr"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(syn::Meta::List(list)) => Some(list.nested),
+            _ => None,
+        })
+        .flatten()
+        .find_map(|nested| match nested {
+            syn::NestedMeta::Meta(syn::Meta::Word(ident)) => match ident.to_string().as_str() {
+                "u8" | "u16" | "u32" | "u64" | "u128" | "i8" | "i16" | "i32" | "i64" | "i128" => {
+                    Some(ident)
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+}
+
+/// Parses either a bare path or one quoted in a string literal, i.e. both
+/// `parser = crate::leb_u32` and `parser = "crate::leb_u32"`.
+pub fn parse_path(input: ParseStream) -> SynResult<syn::Path> {
+    if input.peek(LitStr) {
+        input.parse::<LitStr>()?.parse()
+    } else {
+        input.parse()
+    }
+}
+
+/// Re-types an integer literal as `usize`, which is what nom's counts take.
+pub fn usize_lit(lit: &LitInt) -> LitInt {
+    LitInt::new(lit.value(), syn::IntSuffix::Usize, lit.span())
+}
+
+pub fn pop_parens(input: ParseStream) -> SynResult<ParseBuffer> {
+    let content;
+    parenthesized!(content in input);
+    Ok(content)
+}
+
+pub fn looking_at_match(lookahead: &Lookahead1) -> bool {
+    lookahead.peek(kw::range)
+        || lookahead.peek(kw::val)
+        || lookahead.peek(kw::values)
+        || lookahead.peek(kw::magic)
+        || lookahead.peek(Token![default])
+}
+
+pub fn looking_at_parser(lookahead: &Lookahead1) -> bool {
+    lookahead.peek(kw::parser)
+        || lookahead.peek(kw::skip)
+        || lookahead.peek(kw::iter)
+        || lookahead.peek(kw::switch)
+        || lookahead.peek(kw::bits)
+        || lookahead.peek(kw::until)
+}
+
+pub fn looking_at_config(lookahead: &Lookahead1) -> bool {
+    lookahead.peek(kw::endian)
+        || lookahead.peek(kw::length)
+        || lookahead.peek(kw::length_parser)
+        || lookahead.peek(kw::length_writer)
+        || lookahead.peek(kw::count)
+        || lookahead.peek(kw::size)
+        || lookahead.peek(kw::complete)
+        || lookahead.peek(kw::keep_terminator)
+        || lookahead.peek(kw::nonzero)
+        || lookahead.peek(kw::discriminant)
+        || lookahead.peek(kw::ctx)
+        || lookahead.peek(kw::with_ctx)
+        || lookahead.peek(kw::cond)
+        || lookahead.peek(kw::selector)
+        || lookahead.peek(kw::padding)
+        || lookahead.peek(kw::encoding)
+}
+
+pub fn looking_at_effect(lookahead: &Lookahead1) -> bool {
+    lookahead.peek(kw::debug)
+        || lookahead.peek(kw::tag)
+        || lookahead.peek(kw::call)
+        || lookahead.peek(kw::take)
+}
+
+mod kw {
+    use syn::custom_keyword;
+
+    custom_keyword!(snom);
+
+    custom_keyword!(range);
+    custom_keyword!(start);
+    custom_keyword!(skip);
+    custom_keyword!(end);
+
+    custom_keyword!(val);
+    custom_keyword!(values);
+    custom_keyword!(magic);
+    custom_keyword!(slice);
+    custom_keyword!(switch);
+    custom_keyword!(parser);
+    custom_keyword!(iter);
+    custom_keyword!(bits);
+    custom_keyword!(tag);
+    custom_keyword!(complete);
+
+    custom_keyword!(call);
+    custom_keyword!(take);
+    custom_keyword!(debug);
+
+    custom_keyword!(writer);
+
+    custom_keyword!(endian);
+    custom_keyword!(length);
+    custom_keyword!(length_parser);
+    custom_keyword!(length_writer);
+    custom_keyword!(count);
+    custom_keyword!(size);
+    custom_keyword!(until);
+    custom_keyword!(keep_terminator);
+    custom_keyword!(nonzero);
+    custom_keyword!(discriminant);
+    custom_keyword!(ctx);
+    custom_keyword!(with_ctx);
+    custom_keyword!(cond);
+    custom_keyword!(selector);
+    custom_keyword!(padding);
+    custom_keyword!(encoding);
+}
+
+/// A run of bytes, written `[0x00, 0x00]` or `b"\r\n"`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Bytes {
+    List {
+        bracket_token: syn::token::Bracket,
+        values: Punctuated<LitInt, Token![,]>,
+    },
+    Str(syn::LitByteStr),
+}
+
+impl Bytes {
+    pub fn peek(input: ParseStream) -> bool {
+        input.peek(syn::token::Bracket) || input.peek(syn::LitByteStr)
+    }
+
+    pub fn value(&self) -> Vec<u8> {
+        match self {
+            Bytes::List { values, .. } => values.iter().map(|v| v.value() as u8).collect(),
+            Bytes::Str(bytes) => bytes.value(),
+        }
+    }
+
+    pub fn span(&self) -> proc_macro2::Span {
+        match self {
+            Bytes::List { bracket_token, .. } => bracket_token.span,
+            Bytes::Str(bytes) => bytes.span(),
+        }
+    }
+}
+
+impl Parse for Bytes {
+    fn parse(input: ParseStream) -> SynResult<Self> {
+        let lookahead = input.lookahead1();
+
+        if lookahead.peek(syn::token::Bracket) {
+            let content;
+            let bracket_token = syn::bracketed!(content in input);
+            let values = content.parse_terminated(LitInt::parse)?;
+
+            if values.is_empty() {
+                return Err(syn::Error::new(
+                    bracket_token.span,
+                    "expected at least one byte",
+                ));
+            }
+
+            if let Some(value) = values.iter().find(|v| v.value() > 0xFF) {
+                return Err(syn::Error::new(
+                    value.span(),
+                    format!("{} does not fit in a byte", value.value()),
+                ));
+            }
+
+            Ok(Bytes::List {
+                bracket_token,
+                values,
+            })
+        } else if lookahead.peek(syn::LitByteStr) {
+            let bytes: syn::LitByteStr = input.parse()?;
+
+            if bytes.value().is_empty() {
+                return Err(syn::Error::new(bytes.span(), "expected at least one byte"));
+            }
+
+            Ok(Bytes::Str(bytes))
+        } else {
+            Err(lookahead.error())
+        }
+    }
+}
+
+/// Expands to a `&[u8]` expression.
+impl ToTokens for Bytes {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let repr = match self {
+            Bytes::List { values, .. } => quote! { &[#(#values),*][..] },
+            Bytes::Str(bytes) => quote! { &#bytes[..] },
+        };
+
+        tokens.extend(repr);
+    }
+}
+
+/// What ends an `until` field: a run of bytes in front of the next
+/// element, or an element matching a pattern.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Terminator {
+    Bytes(Bytes),
+    Pattern(syn::Pat),
+}
+
+impl Parse for Terminator {
+    fn parse(input: ParseStream) -> SynResult<Self> {
+        if Bytes::peek(input) {
+            Ok(Terminator::Bytes(input.parse()?))
+        } else {
+            Ok(Terminator::Pattern(input.parse()?))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TagEither {
+    Slice(syn::Ident),
+    Values(Punctuated<LitInt, Token![,]>),
+}
+
+impl Parse for TagEither {
+    fn parse(input: ParseStream) -> SynResult<Self> {
+        let lookahead = input.lookahead1();
+
+        if lookahead.peek(syn::Ident) {
+            Ok(TagEither::Slice(input.parse()?))
+        } else if lookahead.peek(LitInt) {
+            Ok(TagEither::Values(input.parse_terminated(LitInt::parse)?))
+        } else {
+            Err(lookahead.error())
+        }
+    }
+}
+
+// pub enum Either<A: Parse + Token + Peek, B: Parse + Token + Peek> {
+//     Left(A),
+//     Right(B),
+// }
+
+// impl<A, B> Parse for Either<A, B>
+// where
+//     A: Token + Parse,
+//     B: Token + Parse
+//   {
+//     fn parse(input: ParseStream) -> SynResult<Self> {
+//         let lookahead = input.lookahead1();
+
+//         if lookahead.peek(A::Token) {
+//             Ok(Either::Left(input.parse::<A>()?))
+//         } else if lookahead.peek(B::Token) {
+//             Ok(Either::Right(input.parse::<B>()?))
+//         } else {
+//             Err(lookahead.error())
+//         }
+//     }
+// }
+
+/// Attribute usage:
+///
+/// #[derive(StructNom)]
+/// #[snom(switch = le_u8)]
+/// pub enum Instr {
+///     #[snom(range(start = 1))]
+///     Nop, // 1
+///     If,  // 2
+///     #[snom(parser = "crate::my_parser")]
+///     Add, // 3
+///     #[snom(range(skip))]
+///     Sub, // 5
+///     #[snom(range(skip = 3))]
+///     Skip3, // 9
+///     #[snom(range(end = 10))]
+///     Equal, // 10
+///     #[snom(val = "0x0F")]
+///     Another, // 15
+///     #[snom(values(0x20, 0x21))]
+///     Jump(#[snom(discriminant)] u8), // 32 or 33
+///     #[snom(range = 0x60..=0x7F)]
+///     Push(#[snom(discriminant)] u8), // 96 through 127
+///     #[snom(default)]
+///     Unknown(u8), // anything else
+/// }
+///
+/// #[derive(StructNom)]
+/// #[snom(selector = u8)]
+/// pub enum Record {
+///     #[snom(val = 1)]
+///     Address(u32),
+///     #[snom(val = 2)]
+///     Name(#[snom(until = [0x00])] String),
+/// }
+///
+/// #[derive(StructNom)]
+/// pub enum Chunk {
+///     #[snom(magic = b"IHDR")]
+///     Header(Header),
+///     #[snom(magic = [0x49, 0x45, 0x4E, 0x44])]
+///     End,
+/// }
+///
+/// #[derive(StructNom)]
+/// #[snom(ctx = u16)]
+/// pub struct Example<T> {
+///     #[snom(debug = "0x{:x?}")]
+///     #[snom(parser = "crate::leb_u32")]
+///     #[snom(writer = crate::write_leb_u32)]
+///     foo: u32,
+///     #[snom(skip)]
+///     bar: Vec<T>,
+///     #[snom(until = [0x00])]
+///     #[snom(encoding = latin1)]
+///     baz: String,
+///     #[snom(length = u32)]
+///     counted: Vec<u16>,
+///     #[snom(count = foo * 2)]
+///     pairs: Vec<u8>,
+///     #[snom(size = foo)]
+///     #[snom(complete)]
+///     body: Body,
+///     #[snom(size = 8)]
+///     #[snom(padding = b" ")]
+///     name: String,
+///     #[snom(until = Instr::Equal)]
+///     #[snom(keep_terminator)]
+///     code: Vec<Instr>,
+///     #[snom(nonzero)]
+///     flag: bool,
+///     #[snom(with_ctx = foo)]
+///     versioned: Versioned,
+///     #[snom(cond = foo > 1)]
+///     extension: Option<u32>,
+///     #[snom(selector = foo as u8)]
+///     record: Record,
+///     #[snom(debug)]
+///     #[snom(iter)]
+///     quxe: Vec<Instr>
+/// }
+///
+#[allow(dead_code)]
+pub struct Dummy;
+// pub fn parse_attr(attr: &syn::Attribute) -> Option<Vec<syn::NestedMeta>> {
+
+//     None
+// }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse2;
+    use syn::{parse_quote, Attribute};
+
+    #[test]
+    fn range_start() {
+        let attr: Attribute = parse_quote! { #[snom(range(start = 1))] };
+        assert!(is_structnom_attr(&attr));
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert!(snom_arg.match_arg().is_some());
+    }
+
+    #[test]
+    fn range_end() {
+        let attr: Attribute = parse_quote! { #[snom(range(end = 1))] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert!(snom_arg.match_arg().is_some());
+    }
+
+    #[test]
+    fn range_skip() {
+        let attr_1: Attribute = parse_quote! { #[snom(range(skip))] };
+        let snom_arg = parse2::<SnomArg>(attr_1.tts).unwrap();
+
+        assert!(snom_arg.match_arg().is_some());
+
+        let attr_2: Attribute = parse_quote! { #[snom(range(skip = 5))] };
+        let snom_arg = parse2::<SnomArg>(attr_2.tts).unwrap();
+
+        assert!(snom_arg.match_arg().is_some());
+    }
+
+    #[test]
+    fn val() {
+        let attr: Attribute = parse_quote! { #[snom(val = 10)] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert!(snom_arg.match_arg().is_some());
+    }
+
+    #[test]
+    fn values() {
+        let attr: Attribute = parse_quote! { #[snom(values(0x01, 0x02, 0x03))] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert!(snom_arg.match_arg().is_some());
+    }
+
+    #[test]
+    fn ranges() {
+        let attr: Attribute = parse_quote! { #[snom(range = 0x10..=0x1F, 0x30..0x38)] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        match snom_arg.match_arg() {
+            Some(MatchArg::Ranges { ranges, .. }) => {
+                let bounds: Vec<_> = ranges.iter().map(IntRange::bounds).collect();
+
+                assert_eq!(bounds, [Some((0x10, 0x1F)), Some((0x30, 0x37))]);
+            }
+            arg => panic!("expected ranges, got {:?}", arg),
+        }
+
+        let attr: Attribute = parse_quote! { #[snom(range = 4..4)] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert!(matches!(
+            snom_arg.match_arg(),
+            Some(MatchArg::Ranges { ranges, .. }) if ranges[0].bounds().is_none()
+        ));
+    }
+
+    #[test]
+    fn magic() {
+        for attr in &[
+            parse_quote! { #[snom(magic = b"IHDR")] },
+            parse_quote! { #[snom(magic = [0x49, 0x48, 0x44, 0x52])] },
+        ] {
+            let attr: &Attribute = attr;
+            let snom_arg = parse2::<SnomArg>(attr.tts.clone()).unwrap();
+
+            match snom_arg.match_arg() {
+                Some(MatchArg::Magic { value, .. }) => assert_eq!(value.value(), b"IHDR"),
+                arg => panic!("expected a magic, got {:?}", arg),
+            }
+        }
+
+        let attr: Attribute = parse_quote! { #[snom(magic = [0x49, 0x148])] };
+        assert!(parse2::<SnomArg>(attr.tts).is_err());
+    }
+
+    #[test]
+    fn default() {
+        let attr: Attribute = parse_quote! { #[snom(default)] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert!(matches!(
+            snom_arg.match_arg(),
+            Some(MatchArg::Default { .. })
+        ));
+    }
+
+    #[test]
+    fn repr() {
+        let attrs: Vec<Attribute> = vec![
+            parse_quote! { #[derive(Debug)] },
+            parse_quote! { #[repr(C, u16)] },
+        ];
+
+        assert_eq!(find_repr(&attrs).unwrap(), "u16");
+        assert!(find_repr(&[parse_quote! { #[repr(usize)] }]).is_none());
+    }
+
+    #[test]
+    fn skip() {
+        let attr: Attribute = parse_quote! { #[snom(skip)] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert!(snom_arg.value_arg().is_some());
+    }
+
+    #[test]
+    fn debug() {
+        let attr: Attribute = parse_quote! { #[snom(debug)] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert!(snom_arg.effect_arg().is_some());
+
+        let attr: Attribute = parse_quote! { #[snom(debug = "0x{:x?}")] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert!(snom_arg.effect_arg().is_some());
+    }
+
+    #[test]
+    fn iter() {
+        let attr: Attribute = parse_quote! { #[snom(iter)] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert!(snom_arg.value_arg().is_some());
+    }
+
+    #[test]
+    fn writer() {
+        let attr: Attribute = parse_quote! { #[snom(writer = crate::write_leb_u32)] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert!(snom_arg.write_arg().is_some());
+    }
+
+    #[test]
+    fn endian() {
+        let attr: Attribute = parse_quote! { #[snom(endian = big)] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert_eq!(find_endian(&[snom_arg]), Some(Endian::Big));
+
+        let attr: Attribute = parse_quote! { #[snom(endian = "native")] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert_eq!(find_endian(&[snom_arg]), Some(Endian::Native));
+
+        let attr: Attribute = parse_quote! { #[snom(endian = ctx)] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert_eq!(find_endian(&[snom_arg]), Some(Endian::Ctx));
+    }
+
+    #[test]
+    fn length() {
+        let attr: Attribute = parse_quote! { #[snom(length = u32)] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert!(find_length(&[snom_arg]).is_some());
+
+        let attr: Attribute = parse_quote! { #[snom(length_parser = crate::leb_u32)] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert!(find_length(&[snom_arg]).is_some());
+
+        let attr: Attribute = parse_quote! { #[snom(length_writer = crate::write_leb_u32)] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert!(find_length_writer(&[snom_arg]).is_some());
+
+        let attr: Attribute = parse_quote! { #[snom(count = header.count as usize)] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert!(find_length(&[snom_arg]).is_some());
+    }
+
+    #[test]
+    fn size() {
+        let attr: Attribute = parse_quote! { #[snom(size = len - 2)] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert!(find_size(&[snom_arg]).is_some());
+
+        let attr: Attribute = parse_quote! { #[snom(complete)] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert!(find_complete(&[snom_arg]).is_some());
+    }
+
+    #[test]
+    fn ctx() {
+        let attr: Attribute = parse_quote! { #[snom(ctx = Header)] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert!(find_ctx(&[snom_arg]).is_some());
+
+        let attr: Attribute = parse_quote! { #[snom(with_ctx = (version, flags & 1))] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert!(find_with_ctx(&[snom_arg]).is_some());
+    }
+
+    #[test]
+    fn selector() {
+        let attr: Attribute = parse_quote! { #[snom(selector = rtype)] };
+        let snom_args = [parse2::<SnomArg>(attr.tts).unwrap()];
+
+        assert!(find_selector(&snom_args).is_some());
+        assert!(find_with_ctx(&snom_args).is_some());
+
+        // On a type it names the discriminant's type, which need not be an
+        // expression.
+        let attrs: Vec<Attribute> = vec![
+            parse_quote! { #[snom(selector = Option<u8>)] },
+            parse_quote! { #[snom(selector = &'static [u8])] },
+        ];
+        let snom_args = parse_type_args(&attrs).unwrap();
+
+        assert!(snom_args
+            .iter()
+            .all(|arg| matches!(arg.config_arg(), Some(ConfigArg::SelectorType { .. }))));
+        assert!(find_with_ctx(&snom_args).is_none());
+    }
+
+    #[test]
+    fn cond() {
+        let attr: Attribute = parse_quote! { #[snom(cond = flags & 0x1 != 0)] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert!(find_cond(&[snom_arg]).is_some());
+    }
+
+    #[test]
+    fn nonzero() {
+        let attr: Attribute = parse_quote! { #[snom(nonzero)] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert!(find_nonzero(&[snom_arg]).is_some());
+    }
+
+    #[test]
+    fn discriminant() {
+        let attr: Attribute = parse_quote! { #[snom(discriminant)] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert!(find_discriminant(&[snom_arg]).is_some());
+    }
+
+    #[test]
+    fn until() {
+        for attr in &[
+            parse_quote! { #[snom(until = [0x00])] },
+            parse_quote! { #[snom(until = b"\r\n")] },
+            parse_quote! { #[snom(until = Instr::End { .. })] },
+        ] {
+            let attr: &Attribute = attr;
+            let snom_arg = parse2::<SnomArg>(attr.tts.clone()).unwrap();
+
+            assert!(snom_arg.value_arg().is_some());
+        }
+
+        let attr: Attribute = parse_quote! { #[snom(keep_terminator)] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert!(find_keep_terminator(&[snom_arg]).is_some());
+    }
+
+    #[test]
+    fn encoding() {
+        let attr: Attribute = parse_quote! { #[snom(encoding = "UTF-16LE")] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert_eq!(find_encoding(&[snom_arg]), Some(Encoding::Utf16Le));
+
+        let attr: Attribute = parse_quote! { #[snom(encoding = latin1)] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert_eq!(find_encoding(&[snom_arg]), Some(Encoding::Latin1));
+
+        let attr: Attribute = parse_quote! { #[snom(encoding = ebcdic)] };
+
+        assert!(parse2::<SnomArg>(attr.tts).is_err());
+
+        let attr: Attribute = parse_quote! { #[snom(padding = [0x20])] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert!(find_padding(&[snom_arg]).is_some());
+    }
+
+    #[test]
+    fn parser() {
+        let attr: Attribute = parse_quote! { #[snom(parser = "crate::leb_u32")] };
+        let snom_arg = parse2::<SnomArg>(attr.tts).unwrap();
+
+        assert!(snom_arg.value_arg().is_some());
+    }
+}
@@ -0,0 +1,2467 @@
+use quote::{quote, quote_spanned};
+
+use syn::visit::{self, Visit};
+use syn::{
+    spanned::Spanned, Attribute, DataEnum, DataStruct, Error, Fields, FieldsNamed, FieldsUnnamed,
+    Generics, Ident, LitInt, Result as SynResult, Variant,
+};
+
+use crate::attr::*;
+
+#[derive(Debug, Clone)]
+pub enum EnumState {
+    Start(LitInt),
+    Middle(LitInt),
+    None,
+}
+
+/// Discriminants `start..=end` matched by `variant`.
+#[derive(Debug, Clone)]
+struct Claim {
+    start: i128,
+    end: i128,
+    variant: Ident,
+}
+
+pub struct EnumGen {
+    name: Ident,
+    args: Vec<SnomArg>,
+    generics: Generics,
+    data: DataEnum,
+    state: EnumState,
+    /// Every discriminant matched so far, to catch overlapping arms.
+    claimed: Vec<Claim>,
+    /// Every `magic` tag matched so far.
+    tags: Vec<(Vec<u8>, Ident)>,
+    /// Whether the variants are told apart by `magic` tags.
+    magic: bool,
+    /// The type of the discriminant a parent passes in, for enums that do
+    /// not read their own.
+    selector: Option<syn::Type>,
+    /// The integer type of `#[repr(..)]`, read when there is no `switch`.
+    repr: Option<Ident>,
+    /// The discriminant Rust gives each variant, if the enum has a `repr`
+    /// or explicit discriminants.
+    discriminants: Vec<(Ident, i128)>,
+}
+
+impl EnumGen {
+    pub fn new(
+        name: Ident,
+        enum_attrs: Vec<Attribute>,
+        generics: Generics,
+        data: DataEnum,
+    ) -> SynResult<EnumGen> {
+        let args = parse_type_args(&enum_attrs)?;
+        check_type_args(&args)?;
+
+        // println!("Enum Args: {:?}", args);
+
+        let selector = match find_selector(&args) {
+            Some(ConfigArg::SelectorType { value, .. }) => {
+                if let Some(arg) = args.iter().find_map(SnomArg::value_arg) {
+                    return Err(Error::new(
+                        arg.span(),
+                        "an enum with a `selector` does not read its own discriminant",
+                    ));
+                }
+
+                if find_ctx(&args).is_some() {
+                    return Err(Error::new(
+                        value.span(),
+                        "the `selector` is already the context of this enum",
+                    ));
+                }
+
+                Some(value.clone())
+            }
+            _ => None,
+        };
+
+        let repr = find_repr(&enum_attrs);
+        let discriminants = rust_discriminants(&data, repr.is_some())?;
+
+        let mut magic = None;
+
+        for variant in data.variants.iter() {
+            for arg in parse_args(&variant.attrs)? {
+                if let SnomArg::Match(MatchArg::Magic { magic_token, .. }) = arg {
+                    magic = magic.or(Some(magic_token));
+                }
+            }
+        }
+
+        if let Some(magic_token) = magic {
+            if selector.is_some() {
+                return Err(Error::new(
+                    magic_token.span,
+                    "an enum with a `selector` cannot match on `magic` tags",
+                ));
+            }
+
+            if let Some(arg) = args.iter().find_map(SnomArg::value_arg) {
+                return Err(Error::new(
+                    arg.span(),
+                    "an enum with `magic` tags reads them itself",
+                ));
+            }
+        }
+
+        Ok(EnumGen {
+            name,
+            args,
+            generics,
+            data,
+            state: EnumState::None,
+            claimed: Vec::new(),
+            tags: Vec::new(),
+            magic: magic.is_some(),
+            selector,
+            repr,
+            discriminants,
+        })
+    }
+
+    pub fn gen_impl(&mut self) -> SynResult<proc_macro2::TokenStream> {
+        let disc = match (&self.selector, self.magic) {
+            (Some(_), _) => quote! { let __snom_disc = ctx; },
+            // Magic arms match the input itself and rebind the input past the tag.
+            (None, true) => quote! { let __snom_disc = __snom_input; },
+            (None, false) => {
+                let switch_func = self.get_switch_func()?;
+                quote! { let (__snom_input, __snom_disc) = #switch_func?; }
+            }
+        };
+        let mut parsers: Vec<_> = Vec::new();
+        let mut fallback: Option<(&Ident, proc_macro2::TokenStream)> = None;
+        let variants = self.data.variants.clone();
+
+        for variant in variants.iter() {
+            let snom_args = parse_args(&variant.attrs)?;
+            let match_arg = snom_args.iter().find_map(SnomArg::match_arg);
+            let default = match_arg.filter(|arg| matches!(arg, MatchArg::Default { .. }));
+            let parser = self.gen_variant_parser(variant.clone())?;
+            let len = match match_arg {
+                Some(MatchArg::Magic { value, .. }) => value.value().len(),
+                _ => 0,
+            };
+
+            match (default, &fallback) {
+                (Some(arg), Some((other, _))) => {
+                    return Err(Error::new(
+                        arg.span(),
+                        format!("`{}` is already the `default` variant", other),
+                    ))
+                }
+                (Some(_), None) => fallback = Some((&variant.ident, parser)),
+                (None, _) if !parser.is_empty() => parsers.push((len, parser)),
+                (None, _) => {}
+            }
+        }
+
+        // A tag that is a prefix of another must be tried after it.
+        parsers.sort_by_key(|(len, _)| std::cmp::Reverse(*len));
+        let parsers = parsers.into_iter().map(|(_, parser)| parser);
+
+        let kind = if self.magic {
+            quote! { Tag }
+        } else {
+            quote! { Switch }
+        };
+
+        // Unknown discriminants go to the `default` variant if there is one.
+        let fallback = match fallback {
+            Some((_, parser)) if !parser.is_empty() => parser,
+            _ => quote! {
+                _ => Err(::structnom::nom::Err::Error(::structnom::nom::error::make_error(
+                    __snom_start,
+                    ::structnom::nom::error::ErrorKind::#kind,
+                )))
+            },
+        };
+
+        let endian = gen_endian_override(&self.args);
+        let name = &self.name;
+        let (generics, lifetime) = parse_generics(&self.generics);
+        let ctx = self.selector.as_ref().or_else(|| find_ctx(&self.args));
+        let (parse_trait, parse_entry, parse_sig) = gen_parse_sig(ctx, &lifetime);
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        let (_, ty_generics, _) = self.generics.split_for_impl();
+
+        let span = name.span();
+        let expanded = quote_spanned! {span=>
+            impl #impl_generics #parse_trait for #name #ty_generics #where_clause {
+                #parse_entry
+
+                #[allow(unused_variables)]
+                #parse_sig {
+                    #endian
+                    let __snom_start = __snom_input;
+                    #disc
+
+                    match __snom_disc {
+                        #(#parsers,)*
+                        #fallback,
+                    }
+                }
+            }
+        };
+
+        Ok(expanded)
+    }
+
+    pub fn gen_write_impl(&mut self) -> SynResult<proc_macro2::TokenStream> {
+        let disc_writer = self.get_switch_writer()?;
+
+        // Replay the match arms from the start so the discriminants line
+        // up with the arms the parser matches on.
+        self.state = EnumState::None;
+        self.claimed.clear();
+        self.tags.clear();
+
+        let mut arms = Vec::new();
+
+        for variant in self.data.variants.clone() {
+            arms.push(self.gen_variant_writer(variant, &disc_writer)?);
+        }
+
+        let endian = gen_endian_override(&self.args);
+        let name = &self.name;
+        let generics = write_generics(&self.generics);
+        let ctx = self.selector.as_ref().or_else(|| find_ctx(&self.args));
+        let (write_trait, write_entry, write_sig) = gen_write_sig(ctx);
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        let (_, ty_generics, _) = self.generics.split_for_impl();
+
+        let span = name.span();
+        let expanded = quote_spanned! {span=>
+            impl #impl_generics #write_trait for #name #ty_generics #where_clause {
+                #write_entry
+
+                #[allow(unused_variables)]
+                #write_sig {
+                    #endian
+                    match self {
+                        #(#arms)*
+                    }
+
+                    Ok(())
+                }
+            }
+        };
+
+        Ok(expanded)
+    }
+
+    /// Decides how the discriminant of each variant gets written.
+    ///
+    /// An explicit `#[snom(writer = path)]` wins, otherwise the switch
+    /// parser's name (`le_u8`, `be_u16`, ...) decides the byte layout.
+    fn get_switch_writer(&self) -> SynResult<DiscWriter> {
+        if self.selector.is_some() {
+            return Ok(DiscWriter::Selector);
+        }
+
+        if self.magic {
+            return Ok(DiscWriter::Magic);
+        }
+
+        if let Some(WriteArg::Writer { value, .. }) = self.args.iter().find_map(SnomArg::write_arg)
+        {
+            return Ok(DiscWriter::Func(value.clone()));
+        }
+
+        // Rejects enums without a discriminant to write.
+        self.get_switch_func()?;
+
+        let switch = self
+            .args
+            .iter()
+            .filter_map(SnomArg::value_arg)
+            .find_map(|arg| {
+                if let ValueArg::Switch { value, .. } = arg {
+                    Some(value)
+                } else {
+                    None
+                }
+            });
+
+        let switch = match (switch, &self.repr) {
+            (Some(switch), _) => switch,
+            (None, Some(repr)) => return Ok(DiscWriter::Repr(repr.clone())),
+            (None, None) => unreachable!("checked by `get_switch_func`"),
+        };
+
+        let func = match switch.segments.iter().last() {
+            Some(segment) => segment.ident.to_string(),
+            None => String::new(),
+        };
+
+        let primitive = if let Some(ty) = func.strip_prefix("le_") {
+            Some(("to_le_bytes", ty))
+        } else {
+            func.strip_prefix("be_").map(|ty| ("to_be_bytes", ty))
+        };
+
+        match primitive {
+            Some((to_bytes, ty @ ("u8" | "i8" | "u16" | "i16" | "u32" | "i32" | "u64" | "i64" | "u128" | "i128"))) => {
+                let span = switch.span();
+
+                Ok(DiscWriter::Primitive {
+                    ty: Ident::new(ty, span),
+                    to_bytes: Ident::new(to_bytes, span),
+                })
+            }
+            _ => Err(Error::new(
+                switch.span(),
+                format!(
+                    "`{}` cannot be written back, give the enum a `#[snom(writer = path)]` for its discriminant",
+                    func
+                ),
+            )),
+        }
+    }
+
+    fn gen_variant_writer(
+        &mut self,
+        variant: Variant,
+        disc_writer: &DiscWriter,
+    ) -> SynResult<proc_macro2::TokenStream> {
+        let snom_args = parse_args(&variant.attrs)?;
+
+        let match_arg = snom_args.iter().find_map(SnomArg::match_arg);
+        let value_arg = snom_args.iter().find_map(SnomArg::value_arg);
+        let write_arg = snom_args.iter().find_map(SnomArg::write_arg);
+
+        let match_arm = self.handle_match_arm(match_arg, &variant)?;
+        let arm = match_arm.clone();
+
+        // `values(..)` and `range = ..` match several discriminants, any of
+        // them will do. A selector is checked against all of them instead.
+        let disc = match (disc_writer, match_arg) {
+            (DiscWriter::Selector, _) => match_arm,
+            (_, Some(MatchArg::Values { values, .. })) => {
+                let first = values.first().map(|pair| pair.into_value());
+                quote! { #first }
+            }
+            (_, Some(MatchArg::Ranges { ranges, .. })) => {
+                let first = &ranges[0].start;
+                quote! { #first }
+            }
+            (_, Some(MatchArg::Magic { value, .. })) => quote! { #value },
+            _ => match_arm,
+        };
+
+        let name = &self.name;
+        let variant_ident = &variant.ident;
+        let field_gen = FieldsGen::new(
+            &self.name,
+            Some(&variant.ident),
+            &variant.fields,
+            &self.args,
+        )
+        .with_disc_field(disc_field(match_arg, &variant, self.magic)?);
+        let mut pattern = quote! { #name::#variant_ident { .. } };
+        let variant_span = variant.span();
+
+        let body = match (value_arg, write_arg) {
+            (_, Some(WriteArg::Writer { value, .. })) => quote! { #value(self, __snom_out)?; },
+            (Some(ValueArg::Skip { .. }), None) => {
+                let error = format!("{} cannot be written", variant.ident);
+
+                quote! {
+                    return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, #error));
+                }
+            }
+            (Some(arg @ ValueArg::Parser { .. }), None) => return Err(parser_without_writer(arg)),
+            (Some(arg), None) => {
+                return Err(Error::new(
+                    arg.span(),
+                    "unsupported argument on an enum variant",
+                ))
+            }
+            (None, None) => {
+                pattern = field_gen.gen_pattern();
+                field_gen.gen_writer()?
+            }
+        };
+
+        // A captured discriminant is written back as it was read, as long
+        // as it still selects this variant.
+        let disc = match (&field_gen.disc_field, match_arg) {
+            (Some(ident), _) => {
+                let error = format!("`{}` does not select {}", ident, variant.ident);
+                let disc = disc_writer.gen_write_value(quote! { *#ident });
+                pattern = field_gen.gen_pattern();
+
+                match match_arg {
+                    Some(MatchArg::Default { .. }) => disc,
+                    _ => quote! {
+                        if !matches!(*#ident, #arm) {
+                            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, #error));
+                        }
+                        #disc
+                    },
+                }
+            }
+            // The `default` variant of a `magic` enum writes its own tag.
+            (None, Some(MatchArg::Default { .. })) => quote! {},
+            (None, _) => disc_writer.gen_write(disc),
+        };
+        let endian = gen_endian_override(&snom_args);
+
+        let expanded = quote_spanned! {variant_span=>
+            #pattern => {
+                #disc
+                #endian
+                #body
+            }
+        };
+
+        Ok(expanded)
+    }
+
+    /// The call reading the discriminant, the `switch` parser or else the
+    /// `repr` type in the current byte order.
+    fn get_switch_func(&self) -> SynResult<proc_macro2::TokenStream> {
+        let possible: Vec<&ValueArg> = self.args.iter().filter_map(SnomArg::value_arg).collect();
+
+        // println!("Possible Attrs {:?}", possible);
+
+        match (possible.first(), &self.repr) {
+            (Some(ValueArg::Switch { value, .. }), _) => Ok(quote! { #value(__snom_input) }),
+            (Some(arg), _) => Err(Error::new(
+                arg.span(),
+                "enums only accept a `switch` parser",
+            )),
+            (None, Some(repr)) => Ok(quote! {
+                <#repr as ::structnom::StructNom>::nom_endian(__snom_input, __snom_endian)
+            }),
+            (None, None) => Err(Error::new(
+                self.name.span(),
+                "enums need a `#[snom(switch = parser)]` or a `#[repr(..)]` for their discriminant",
+            )),
+        }
+    }
+
+    pub fn gen_variant_parser(&mut self, variant: Variant) -> SynResult<proc_macro2::TokenStream> {
+        let snom_args = parse_args(&variant.attrs)?;
+
+        let match_arg = snom_args.iter().find_map(SnomArg::match_arg);
+        let value_arg = snom_args.iter().find_map(SnomArg::value_arg);
+
+        if let Some(effect) = snom_args.iter().find_map(SnomArg::effect_arg) {
+            return Err(Error::new(
+                effect.span(),
+                "effects are not supported on enum variants",
+            ));
+        }
+
+        //         Parser {
+        //     parser_token: kw::parser,
+        //     eq_token: Token![=],
+        //     value: syn::Path,
+        // },
+        // Skip {
+        //     skip_token: kw::skip,
+        // },
+        // Iter {
+        //     iter_token: kw::iter,
+        // },
+        let match_arm = self.handle_match_arm(match_arg, &variant)?;
+        let variant_span = variant.span();
+
+        match value_arg {
+            Some(ValueArg::Parser { value, .. }) => {
+                return Ok(quote_spanned!(variant_span=> #match_arm => #value(__snom_input)))
+            }
+            Some(ValueArg::Skip { .. }) => {
+                // println!("Empty: {:#?}", quote_spanned!(variant_span=> ));
+
+                return Ok(quote_spanned!(variant_span=> ));
+            }
+            Some(arg) => {
+                return Err(Error::new(
+                    arg.span(),
+                    "unsupported argument on an enum variant",
+                ))
+            }
+            None => {}
+        }
+
+        let field_gen = FieldsGen::new(
+            &self.name,
+            Some(&variant.ident),
+            &variant.fields,
+            &self.args,
+        )
+        .with_disc_field(disc_field(match_arg, &variant, self.magic)?);
+        let field_parser = field_gen.gen_parser()?;
+        let endian = gen_endian_override(&snom_args);
+
+        let expanded = quote_spanned! {variant_span=>
+            #match_arm => {
+                #endian
+                #field_parser
+            }
+        };
+
+        Ok(expanded)
+    }
+
+    fn handle_range(&mut self, range: &RangeArg) -> SynResult<LitInt> {
+        // println!("Range State: {:?}, Range Arg: {:?}", self.state, range);
+
+        match range {
+            RangeArg::Start { value, .. } => {
+                if let EnumState::None = self.state.clone() {
+                    self.state = EnumState::Start(value.clone());
+
+                    Ok(value.clone())
+                } else {
+                    Err(Error::new(
+                        range.span(),
+                        "invalid `range(start ...)`, another range is already in progress",
+                    ))
+                }
+            }
+            RangeArg::Skip { value, .. } => {
+                if let EnumState::Start(prev) | EnumState::Middle(prev) = self.state.clone() {
+                    let value = value
+                        .clone()
+                        .unwrap_or(LitInt::new(1, prev.suffix(), prev.span()));
+
+                    let next = prev
+                        .value()
+                        .checked_add(value.value())
+                        .and_then(|next| next.checked_add(1))
+                        .ok_or_else(|| {
+                            Error::new(value.span(), "this skips past the largest discriminant")
+                        })?;
+
+                    let new_lit = LitInt::new(next, value.suffix(), value.span());
+                    self.state = EnumState::Middle(new_lit.clone());
+
+                    Ok(new_lit)
+                } else {
+                    Err(Error::new(
+                        range.span(),
+                        "invalid `range(skip ...)`, a range must be started before one can be skipped",
+                    ))
+                }
+            }
+            RangeArg::End { value, .. } => {
+                if let EnumState::Middle(prev) | EnumState::Start(prev) = self.state.clone() {
+                    if prev.value().checked_add(1) == Some(value.value()) {
+                        self.state = EnumState::None;
+
+                        Ok(value.clone())
+                    } else {
+                        Err(Error::new(
+                            value.span(),
+                            format!(
+                                "invalid `range(end ...)`, the ending value must be {}",
+                                u128::from(prev.value()) + 1
+                            ),
+                        ))
+                    }
+                } else {
+                    Err(Error::new(
+                        range.span(),
+                        "invalid `range(end ...)`, a range must only end after one has been started",
+                    ))
+                }
+            }
+        }
+    }
+
+    fn handle_match_arm(
+        &mut self,
+        match_arm: Option<&MatchArg>,
+        variant: &Variant,
+    ) -> SynResult<proc_macro2::TokenStream> {
+        let span = variant.span();
+
+        match (self.magic, match_arm) {
+            (true, Some(MatchArg::Magic { value, .. })) => {
+                self.claim_tag(&variant.ident, value)?;
+
+                let bytes = value
+                    .value()
+                    .into_iter()
+                    .map(proc_macro2::Literal::u8_unsuffixed);
+
+                return Ok(quote_spanned!(span=> [#(#bytes,)* __snom_input @ ..]));
+            }
+            (true, Some(MatchArg::Default { .. })) | (false, _) => {}
+            (true, arg) => {
+                return Err(Error::new(
+                    arg.map_or(span, MatchArg::span),
+                    "every variant of an enum with `magic` tags needs one",
+                ))
+            }
+        }
+
+        let (arm, bounds) = match match_arm {
+            Some(MatchArg::Range(range)) => {
+                let value = self.handle_range(range)?;
+                let bounds = vec![(value.value().into(), value.value().into())];
+
+                (quote! { #value }, bounds)
+            }
+            Some(MatchArg::Val { value, .. }) => (
+                quote! { #value },
+                vec![(value.value().into(), value.value().into())],
+            ),
+            Some(MatchArg::Values { values, .. }) => {
+                let bounds = values
+                    .iter()
+                    .map(|v| (v.value().into(), v.value().into()))
+                    .collect();
+
+                (quote! { #(#values)|* }, bounds)
+            }
+            Some(MatchArg::Ranges { ranges, .. }) => {
+                let mut bounds = Vec::new();
+                let mut patterns = Vec::new();
+
+                for range in ranges.iter() {
+                    match range.bounds() {
+                        Some((start, end)) => {
+                            // Half-open range patterns need a newer compiler,
+                            // so every range is emitted as `start..=end`.
+                            let suffix = range.start.suffix();
+                            let start_lit = LitInt::new(start, suffix.clone(), range.span());
+                            let end_lit = LitInt::new(end, suffix, range.end.span());
+
+                            patterns.push(quote! { #start_lit..=#end_lit });
+                            bounds.push((start.into(), end.into()));
+                        }
+                        None => {
+                            return Err(Error::new(
+                                range.span(),
+                                "this range of discriminants is empty",
+                            ))
+                        }
+                    }
+                }
+
+                (quote! { #(#patterns)|* }, bounds)
+            }
+            Some(MatchArg::Default { .. }) => return Ok(quote! { _ }),
+            Some(MatchArg::Magic { .. }) => unreachable!("only enums with `magic` tags have them"),
+            None => {
+                // An explicit `Nop = 1` wins over a running `range`, which in
+                // turn wins over the implicit discriminants of a `repr` enum.
+                let rust = self
+                    .discriminants
+                    .iter()
+                    .find(|(ident, _)| *ident == variant.ident)
+                    .map(|(_, value)| *value);
+
+                if let (Some(value), Some(_)) = (rust, &variant.discriminant) {
+                    (int_pattern(value, span), vec![(value, value)])
+                } else if let EnumState::Start(ref mut lit) | EnumState::Middle(ref mut lit) =
+                    self.state
+                {
+                    let next = lit.value().checked_add(1).ok_or_else(|| {
+                        Error::new(
+                            span,
+                            format!("the range has no discriminant left after {}", lit.value()),
+                        )
+                    })?;
+                    let new_lit = LitInt::new(next, lit.suffix(), lit.span());
+                    *lit = new_lit.clone();
+
+                    let bounds = vec![(next.into(), next.into())];
+
+                    (quote_spanned! (span=> #new_lit ), bounds)
+                } else if let Some(value) = rust {
+                    (int_pattern(value, span), vec![(value, value)])
+                } else {
+                    return Err(Error::new(
+                        span,
+                        "variant has no discriminant, add `val`, `values` or start a `range`",
+                    ));
+                }
+            }
+        };
+
+        let span = match_arm.map_or(span, MatchArg::span);
+
+        for (start, end) in bounds {
+            self.claim(&variant.ident, start, end, span)?;
+        }
+
+        Ok(arm)
+    }
+
+    /// Records that `variant` matches `start..=end`. Every discriminant may
+    /// only select one variant, a later arm with it would be unreachable.
+    fn claim(
+        &mut self,
+        variant: &Ident,
+        start: i128,
+        end: i128,
+        span: proc_macro2::Span,
+    ) -> SynResult<()> {
+        let overlap = self
+            .claimed
+            .iter()
+            .find(|claim| claim.start <= end && start <= claim.end);
+
+        if let Some(claim) = overlap {
+            return Err(Error::new(
+                span,
+                format!(
+                    "`{}` matches {}, which `{}` already matches",
+                    variant,
+                    start.max(claim.start),
+                    claim.variant
+                ),
+            ));
+        }
+
+        self.claimed.push(Claim {
+            start,
+            end,
+            variant: variant.clone(),
+        });
+
+        Ok(())
+    }
+
+    /// Records that `variant` matches the tag `magic`, which must be unique.
+    fn claim_tag(&mut self, variant: &Ident, magic: &Bytes) -> SynResult<()> {
+        let tag = magic.value();
+
+        if let Some((_, other)) = self.tags.iter().find(|(claimed, _)| *claimed == tag) {
+            let escaped: String = tag
+                .iter()
+                .flat_map(|&byte| std::ascii::escape_default(byte))
+                .map(char::from)
+                .collect();
+
+            return Err(Error::new(
+                magic.span(),
+                format!(
+                    "`{}` matches b\"{}\", which `{}` already matches",
+                    variant, escaped, other
+                ),
+            ));
+        }
+
+        self.tags.push((tag, variant.clone()));
+
+        Ok(())
+    }
+
+    // fn gen_parser(
+    //     &mut self,
+    //     value_arg: Option<&ValueArg>,
+    //     match_arm: Option<&MatchArg>,
+    //     post_parsers: Vec<&EffectArg>,
+    // ) {
+    //     match value_arg {
+    //         Some(ValueArg::Parser { value, .. }) => {
+
+    //         }
+    //         _ => {}
+    //     }
+    // }
+}
+
+/// Numbers the variants the way Rust does, from an explicit `= 1` or one
+/// past the previous variant. Enums without a `repr` or any explicit
+/// discriminant get none.
+fn rust_discriminants(data: &DataEnum, repr: bool) -> SynResult<Vec<(Ident, i128)>> {
+    if !repr && data.variants.iter().all(|v| v.discriminant.is_none()) {
+        return Ok(Vec::new());
+    }
+
+    // Only an implicit discriminant after the largest one is an error.
+    let mut next = Some(0);
+    let mut discriminants = Vec::new();
+
+    for variant in data.variants.iter() {
+        let value = match variant.discriminant {
+            Some((_, ref expr)) => int_value(expr).ok_or_else(|| {
+                Error::new(
+                    expr.span(),
+                    "only integer literal discriminants can be matched on",
+                )
+            })?,
+            None => next.ok_or_else(|| {
+                Error::new(
+                    variant.ident.span(),
+                    "the discriminant of this variant overflows, give it an explicit one",
+                )
+            })?,
+        };
+
+        next = value.checked_add(1);
+        discriminants.push((variant.ident.clone(), value));
+    }
+
+    Ok(discriminants)
+}
+
+/// The value of an integer literal discriminant such as `1` or `-1`.
+fn int_value(expr: &syn::Expr) -> Option<i128> {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(lit),
+            ..
+        }) => Some(lit.value().into()),
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            expr,
+            ..
+        }) => int_value(expr).map(|value| -value),
+        _ => None,
+    }
+}
+
+/// A pattern matching the discriminant `value`.
+fn int_pattern(value: i128, span: proc_macro2::Span) -> proc_macro2::TokenStream {
+    let lit = LitInt::new(value.unsigned_abs() as u64, syn::IntSuffix::None, span);
+
+    if value < 0 {
+        quote_spanned!(span=> -#lit)
+    } else {
+        quote_spanned!(span=> #lit)
+    }
+}
+
+/// The field of `variant` the discriminant is bound to, the one marked with
+/// `discriminant` or else the first one of a `default` variant. Enums with
+/// `magic` tags have no discriminant to bind, their `default` variant
+/// parses its fields from the unmatched tag on.
+fn disc_field(
+    match_arg: Option<&MatchArg>,
+    variant: &Variant,
+    magic: bool,
+) -> SynResult<Option<Ident>> {
+    let mut marked = None;
+
+    for (i, field) in variant.fields.iter().enumerate() {
+        let snom_args = parse_args(&field.attrs)?;
+        let arg = match find_discriminant(&snom_args) {
+            Some(arg) => arg,
+            None => continue,
+        };
+
+        if marked.is_some() {
+            return Err(Error::new(
+                arg.span(),
+                "only one field can hold the discriminant",
+            ));
+        }
+
+        if magic {
+            return Err(Error::new(
+                arg.span(),
+                "variants matched by `magic` tags have no discriminant to hold",
+            ));
+        }
+
+        if snom_args.len() > 1 {
+            return Err(Error::new(
+                arg.span(),
+                "the discriminant is not parsed, its field takes no other arguments",
+            ));
+        }
+
+        marked = Some(field_ident(i, field));
+    }
+
+    match (marked, match_arg) {
+        (Some(ident), _) => Ok(Some(ident)),
+        (None, _) if magic => Ok(None),
+        (None, Some(MatchArg::Default { default_token })) => match variant.fields.iter().next() {
+            Some(field) => Ok(Some(field_ident(0, field))),
+            None => Err(Error::new(
+                default_token.span,
+                "the `default` variant needs a field to hold the discriminant",
+            )),
+        },
+        (None, _) => Ok(None),
+    }
+}
+
+/// The ident field `i` is bound to, `f_i` for tuple fields.
+fn field_ident(i: usize, field: &syn::Field) -> Ident {
+    match field.ident {
+        Some(ref ident) => ident.clone(),
+        None => Ident::new(&format!("f_{}", i), field.ident.span()),
+    }
+}
+
+/// The error for a `parser` that is written without a matching `writer`.
+fn parser_without_writer(parser: &ValueArg) -> Error {
+    Error::new(
+        parser.span(),
+        "`parser` cannot be written back without a matching `#[snom(writer = path)]`",
+    )
+}
+
+/// Shadows the caller's byte order if the type picked its own. The order of
+/// the outermost call stays around in `__snom_runtime` for `endian = ctx`.
+fn gen_endian_override(args: &[SnomArg]) -> proc_macro2::TokenStream {
+    match find_endian(args) {
+        Some(endian) => quote! { let __snom_endian = #endian; },
+        None => quote! {},
+    }
+}
+
+/// The trait a type's parser implements, the methods that only forward to
+/// the generated one and the signature of that one. A type with a `ctx`
+/// takes the context as an extra argument.
+///
+/// Besides the byte order every parser gets the `Ctx` of the outermost call
+/// as `__snom_runtime`, which it passes on to its fields unchanged.
+fn gen_parse_sig(
+    ctx: Option<&syn::Type>,
+    lifetime: &syn::Lifetime,
+) -> (
+    proc_macro2::TokenStream,
+    proc_macro2::TokenStream,
+    proc_macro2::TokenStream,
+) {
+    match ctx {
+        Some(ctx) => (
+            quote! { ::structnom::StructNomWith<#lifetime, #ctx> },
+            quote! {},
+            quote! {
+                fn nom_with(__snom_input: &#lifetime [u8], ctx: #ctx, __snom_endian: ::structnom::Endian, __snom_runtime: &::structnom::Ctx) -> ::structnom::nom::IResult<&#lifetime [u8], Self>
+            },
+        ),
+        None => (
+            quote! { ::structnom::StructNom<#lifetime> },
+            quote! {
+                fn nom_endian(__snom_input: &#lifetime [u8], __snom_endian: ::structnom::Endian) -> ::structnom::nom::IResult<&#lifetime [u8], Self> {
+                    Self::nom_field(__snom_input, __snom_endian, &::structnom::Ctx::new(__snom_endian))
+                }
+            },
+            quote! {
+                fn nom_field(__snom_input: &#lifetime [u8], __snom_endian: ::structnom::Endian, __snom_runtime: &::structnom::Ctx) -> ::structnom::nom::IResult<&#lifetime [u8], Self>
+            },
+        ),
+    }
+}
+
+/// The writing counterpart of `gen_parse_sig`.
+fn gen_write_sig(
+    ctx: Option<&syn::Type>,
+) -> (
+    proc_macro2::TokenStream,
+    proc_macro2::TokenStream,
+    proc_macro2::TokenStream,
+) {
+    match ctx {
+        Some(ctx) => (
+            quote! { ::structnom::StructNomWriteWith<#ctx> },
+            quote! {},
+            quote! {
+                fn write_with<W: std::io::Write>(&self, __snom_out: &mut W, ctx: #ctx, __snom_endian: ::structnom::Endian, __snom_runtime: &::structnom::Ctx) -> std::io::Result<()>
+            },
+        ),
+        None => (
+            quote! { ::structnom::StructNomWrite },
+            quote! {
+                fn write_endian<W: std::io::Write>(&self, __snom_out: &mut W, __snom_endian: ::structnom::Endian) -> std::io::Result<()> {
+                    self.write_field(__snom_out, __snom_endian, &::structnom::Ctx::new(__snom_endian))
+                }
+            },
+            quote! {
+                fn write_field<W: std::io::Write>(&self, __snom_out: &mut W, __snom_endian: ::structnom::Endian, __snom_runtime: &::structnom::Ctx) -> std::io::Result<()>
+            },
+        ),
+    }
+}
+
+/// Rejects arguments that only make sense on a single field.
+fn check_type_args(args: &[SnomArg]) -> SynResult<()> {
+    let field_only = args.iter().filter_map(SnomArg::config_arg).find(|arg| {
+        matches!(
+            arg,
+            ConfigArg::Count { .. }
+                | ConfigArg::Size { .. }
+                | ConfigArg::Discriminant { .. }
+                | ConfigArg::Complete { .. }
+                | ConfigArg::WithCtx { .. }
+                | ConfigArg::Cond { .. }
+                | ConfigArg::KeepTerminator { .. }
+                | ConfigArg::Nonzero { .. }
+                | ConfigArg::Padding { .. }
+                | ConfigArg::Encoding { .. }
+        )
+    });
+
+    match field_only {
+        Some(arg) => Err(Error::new(
+            arg.span(),
+            "this argument only applies to fields",
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Turns a `count` or `size` expression into a `usize`. Integer literals are
+/// re-typed directly since `ToUsize` cannot pick their type.
+fn gen_usize(expr: &syn::Expr) -> proc_macro2::TokenStream {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(lit),
+            ..
+        }) => {
+            let lit = usize_lit(lit);
+            quote! { #lit }
+        }
+        _ => quote! { ::structnom::nom::ToUsize::to_usize(&(#expr)) },
+    }
+}
+
+/// The bare identifiers `expr` refers to, some of which may be fields.
+fn expr_idents(expr: &syn::Expr) -> Vec<Ident> {
+    struct Idents(Vec<Ident>);
+
+    impl<'ast> Visit<'ast> for Idents {
+        fn visit_expr_path(&mut self, expr: &'ast syn::ExprPath) {
+            let path = &expr.path;
+
+            if expr.qself.is_none() && path.leading_colon.is_none() && path.segments.len() == 1 {
+                self.0.push(path.segments[0].ident.clone());
+            }
+
+            visit::visit_expr_path(self, expr);
+        }
+    }
+
+    let mut idents = Idents(Vec::new());
+    idents.visit_expr(expr);
+
+    idents.0
+}
+
+/// Whether the output of `arg` can be decoded with an `encoding`, the other
+/// parsers produce the field's type directly.
+fn is_encodable(arg: &ValueArg) -> bool {
+    matches!(arg, ValueArg::Iter { .. } | ValueArg::Until { .. })
+}
+
+/// Reads a length prefix into `length`.
+fn gen_length_parser(length: &ConfigArg) -> proc_macro2::TokenStream {
+    match length {
+        ConfigArg::Length { value, .. } => quote! {
+            let (__snom_input, __snom_length) = <#value as ::structnom::StructNom>::nom_endian(__snom_input, __snom_endian)?;
+        },
+        ConfigArg::LengthParser { value, .. } => quote! {
+            let (__snom_input, __snom_length) = #value(__snom_input)?;
+        },
+        _ => quote! {},
+    }
+}
+
+/// Whether `ty` is spelled `Vec<..>`, `String` or a borrowed `&[u8]` or
+/// `&str`, which is what type level defaults like `length` apply to.
+fn is_collection(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(path) => path
+            .path
+            .segments
+            .iter()
+            .last()
+            .is_some_and(|segment| segment.ident == "Vec" || segment.ident == "String"),
+        syn::Type::Reference(_) => true,
+        _ => false,
+    }
+}
+
+/// The length of a `[u8; N]` field, which is copied out of the input in one
+/// go rather than parsed a byte at a time.
+fn byte_array_len(ty: &syn::Type) -> Option<&syn::Expr> {
+    match ty {
+        syn::Type::Array(array) => match &*array.elem {
+            syn::Type::Path(path) if path.qself.is_none() && path.path.is_ident("u8") => {
+                Some(&array.len)
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Whether a field without a parser or length of its own takes its whole
+/// `size` window, as padded and borrowed fields do.
+fn fills_window(args: &[SnomArg], ty: &syn::Type) -> bool {
+    find_size(args).is_some()
+        && (find_padding(args).is_some() || matches!(ty, syn::Type::Reference(_)))
+}
+
+/// The `T` of a `cond` field spelled `Option<T>`.
+fn option_inner<'a>(ty: &'a syn::Type, cond: &syn::Expr) -> SynResult<&'a syn::Type> {
+    option_arg(ty).ok_or_else(|| Error::new(cond.span(), "`cond` only applies to `Option` fields"))
+}
+
+/// Rejects an `Option` field that is parsed by trying `T`. A `None` writes
+/// nothing, so a field after it would be read back as `Some`.
+fn check_bare_option(field: &syn::Field) -> SynResult<()> {
+    let snom_args = parse_args(&field.attrs)?;
+
+    let bare = option_arg(&field.ty).is_some()
+        && find_cond(&snom_args).is_none()
+        && snom_args.iter().find_map(SnomArg::value_arg).is_none()
+        && snom_args.iter().find_map(SnomArg::write_arg).is_none();
+
+    if bare {
+        return Err(Error::new(
+            field.ty.span(),
+            "an `Option` can only be written back as the last field or with a `cond`",
+        ));
+    }
+
+    Ok(())
+}
+
+/// The `T` of a type spelled `Option<T>`.
+fn option_arg(ty: &syn::Type) -> Option<&syn::Type> {
+    match ty {
+        syn::Type::Path(path) => {
+            path.path
+                .segments
+                .iter()
+                .last()
+                .and_then(|segment| match &segment.arguments {
+                    syn::PathArguments::AngleBracketed(args) if segment.ident == "Option" => {
+                        match args.args.first().map(|pair| pair.into_value()) {
+                            Some(syn::GenericArgument::Type(ty)) => Some(ty),
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                })
+        }
+        _ => None,
+    }
+}
+
+/// The generics of a `StructNom` impl and its input lifetime.
+///
+/// A type that borrows from its input names the lifetime as its first
+/// lifetime parameter, `'nom` is added for types that own their data. Type
+/// parameters have to be parseable from the same input.
+fn parse_generics(generics: &Generics) -> (Generics, syn::Lifetime) {
+    let mut generics = generics.clone();
+
+    let first = generics.lifetimes().next().map(|def| def.lifetime.clone());
+
+    let lifetime = match first {
+        Some(lifetime) => lifetime,
+        None => {
+            let lifetime = syn::Lifetime::new("'nom", proc_macro2::Span::call_site());
+            let def = syn::LifetimeDef::new(lifetime.clone());
+
+            generics.params.insert(0, syn::GenericParam::Lifetime(def));
+            lifetime
+        }
+    };
+
+    let params: Vec<_> = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect();
+    let where_clause = generics.make_where_clause();
+
+    for param in params {
+        where_clause
+            .predicates
+            .push(syn::parse_quote!(#param: ::structnom::StructNom<#lifetime>));
+    }
+
+    (generics, lifetime)
+}
+
+/// The generics of a `StructNomWrite` impl, type parameters have to be
+/// writable themselves.
+fn write_generics(generics: &Generics) -> Generics {
+    let mut generics = generics.clone();
+
+    let params: Vec<_> = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect();
+    let where_clause = generics.make_where_clause();
+
+    for param in params {
+        where_clause
+            .predicates
+            .push(syn::parse_quote!(#param: ::structnom::StructNomWrite));
+    }
+
+    generics
+}
+
+/// How an enum writes the discriminant its switch parser reads.
+#[derive(Debug, Clone)]
+pub enum DiscWriter {
+    Primitive {
+        ty: Ident,
+        to_bytes: Ident,
+    },
+    Func(syn::Path),
+    /// The parent writes the discriminant, the variant only has to be one
+    /// its `selector` picks.
+    Selector,
+    /// The `repr` type, written in the current byte order.
+    Repr(Ident),
+    /// The variant's `magic` tag.
+    Magic,
+}
+
+impl DiscWriter {
+    fn gen_write(&self, disc: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        match self {
+            DiscWriter::Primitive { ty, to_bytes } => quote! {
+                let __snom_disc: #ty = #disc;
+                __snom_out.write_all(&__snom_disc.#to_bytes())?;
+            },
+            DiscWriter::Func(func) => quote! {
+                #func(&#disc, __snom_out)?;
+            },
+            DiscWriter::Repr(ty) => quote! {
+                let __snom_disc: #ty = #disc;
+                ::structnom::StructNomWrite::write_endian(&__snom_disc, __snom_out, __snom_endian)?;
+            },
+            DiscWriter::Magic => quote! {
+                __snom_out.write_all(#disc)?;
+            },
+            DiscWriter::Selector => quote! {
+                if !matches!(ctx, #disc) {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        "the selector does not pick this variant",
+                    ));
+                }
+            },
+        }
+    }
+
+    /// Like `gen_write`, but for a discriminant held in a field rather than
+    /// given by a pattern.
+    fn gen_write_value(&self, value: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        match self {
+            DiscWriter::Selector => quote! {
+                if ctx != #value {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        "the selector does not pick this variant",
+                    ));
+                }
+            },
+            _ => self.gen_write(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StructGen {
+    name: Ident,
+    args: Vec<SnomArg>,
+    generics: Generics,
+    data: DataStruct,
+    // state: EnumState,
+}
+
+impl StructGen {
+    pub fn new(
+        name: Ident,
+        enum_attrs: Vec<Attribute>,
+        generics: Generics,
+        data: DataStruct,
+    ) -> SynResult<StructGen> {
+        let args = parse_type_args(&enum_attrs)?;
+        check_type_args(&args)?;
+
+        // println!("Struct Args: {:?}", args);
+
+        if let Some(arg) = args.iter().find_map(SnomArg::match_arg) {
+            return Err(Error::new(
+                arg.span(),
+                "discriminants only apply to enum variants",
+            ));
+        }
+
+        if let Some(selector) = find_selector(&args) {
+            return Err(Error::new(selector.span(), "only enums take a `selector`"));
+        }
+
+        Ok(StructGen {
+            name,
+            args,
+            generics,
+            data,
+        })
+    }
+
+    pub fn gen_impl(&mut self) -> SynResult<proc_macro2::TokenStream> {
+        let value_arg = self.args.iter().find_map(SnomArg::value_arg);
+        let field_parser = match value_arg {
+            Some(ValueArg::Parser { value, .. }) => quote! { #value(__snom_input) },
+            Some(arg) => return Err(Error::new(arg.span(), "unsupported argument on a struct")),
+            None => {
+                let field_gen = FieldsGen::new(&self.name, None, &self.data.fields, &self.args);
+                field_gen.gen_parser()?
+            }
+        };
+
+        let endian = gen_endian_override(&self.args);
+        let name = &self.name;
+        let (generics, lifetime) = parse_generics(&self.generics);
+        let (parse_trait, parse_entry, parse_sig) = gen_parse_sig(find_ctx(&self.args), &lifetime);
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        let (_, ty_generics, _) = self.generics.split_for_impl();
+
+        let span = self.name.span();
+        let expanded = quote_spanned! {span=>
+            impl #impl_generics #parse_trait for #name #ty_generics #where_clause {
+                #parse_entry
+
+                #[allow(unused_variables)]
+                #parse_sig {
+                    #endian
+                    #field_parser
+                }
+            }
+        };
+
+        Ok(expanded)
+    }
+
+    pub fn gen_write_impl(&mut self) -> SynResult<proc_macro2::TokenStream> {
+        let value_arg = self.args.iter().find_map(SnomArg::value_arg);
+        let write_arg = self.args.iter().find_map(SnomArg::write_arg);
+
+        let body = match (value_arg, write_arg) {
+            (_, Some(WriteArg::Writer { value, .. })) => quote! { #value(self, __snom_out)?; },
+            (Some(arg @ ValueArg::Parser { .. }), None) => return Err(parser_without_writer(arg)),
+            (Some(arg), None) => {
+                return Err(Error::new(arg.span(), "unsupported argument on a struct"))
+            }
+            (None, None) => {
+                let field_gen = FieldsGen::new(&self.name, None, &self.data.fields, &self.args);
+                let pattern = field_gen.gen_pattern();
+                let writer = field_gen.gen_writer()?;
+
+                quote! {
+                    let #pattern = self;
+                    #writer
+                }
+            }
+        };
+
+        let endian = gen_endian_override(&self.args);
+        let name = &self.name;
+        let generics = write_generics(&self.generics);
+        let (write_trait, write_entry, write_sig) = gen_write_sig(find_ctx(&self.args));
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        let (_, ty_generics, _) = self.generics.split_for_impl();
+
+        let span = self.name.span();
+        let expanded = quote_spanned! {span=>
+            impl #impl_generics #write_trait for #name #ty_generics #where_clause {
+                #write_entry
+
+                #[allow(unused_variables)]
+                #write_sig {
+                    #endian
+                    #body
+
+                    Ok(())
+                }
+            }
+        };
+
+        Ok(expanded)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FieldsGen<'a> {
+    name: &'a Ident,
+    variant: Option<&'a Ident>,
+    fields: &'a Fields,
+    /// Arguments of the surrounding type, they supply field defaults.
+    type_args: &'a [SnomArg],
+    /// The field holding the enum's discriminant, it is not parsed or
+    /// written itself.
+    disc_field: Option<Ident>,
+}
+
+impl<'a> FieldsGen<'a> {
+    pub fn new(
+        name: &'a Ident,
+        variant: Option<&'a Ident>,
+        fields: &'a Fields,
+        type_args: &'a [SnomArg],
+    ) -> FieldsGen<'a> {
+        FieldsGen {
+            name,
+            variant,
+            fields,
+            type_args,
+            disc_field: None,
+        }
+    }
+
+    pub fn with_disc_field(mut self, disc_field: Option<Ident>) -> FieldsGen<'a> {
+        self.disc_field = disc_field;
+        self
+    }
+
+    pub fn gen_parser(&self) -> SynResult<proc_macro2::TokenStream> {
+        match &self.fields {
+            Fields::Named(named) => self.gen_named_parser(named),
+            Fields::Unnamed(unnamed) => self.gen_unnamed_parser(unnamed),
+            Fields::Unit => self.gen_unit_parser(),
+        }
+    }
+
+    fn gen_named_parser(&self, fields: &FieldsNamed) -> SynResult<proc_macro2::TokenStream> {
+        let mut idents = Vec::new();
+        let mut parsers = Vec::new();
+
+        for field in fields.named.iter() {
+            let field_ident = field.ident.clone().expect("Named Fields must be named");
+            let parser = self.gen_field_parser(&field_ident, field)?;
+
+            idents.push(field_ident);
+            parsers.push(parser);
+        }
+
+        let name = self.gen_name();
+
+        Ok(quote! {
+            {
+                #(#parsers)*
+                Ok((__snom_input, #name { #(#idents),* }))
+            }
+        })
+    }
+
+    fn gen_unnamed_parser(&self, fields: &FieldsUnnamed) -> SynResult<proc_macro2::TokenStream> {
+        let mut idents = Vec::new();
+        let mut parsers = Vec::new();
+
+        for (i, field) in fields.unnamed.iter().enumerate() {
+            let field_name = format!("f_{}", i);
+            let field_ident = Ident::new(&field_name, field.ident.span());
+            let parser = self.gen_field_parser(&field_ident, field)?;
+
+            idents.push(field_ident);
+            parsers.push(parser);
+        }
+
+        let name = self.gen_name();
+
+        Ok(quote! {
+            {
+                #(#parsers)*
+                Ok((__snom_input, #name ( #(#idents),* )))
+            }
+        })
+    }
+
+    /// The idents the fields are bound to, in declaration order.
+    fn field_idents(&self) -> Vec<Ident> {
+        self.fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| field_ident(i, field))
+            .collect()
+    }
+
+    /// The fields a `count` or `size` of `ident` reads, which must all be
+    /// parsed before it.
+    fn earlier_fields(&self, ident: &Ident, expr: &syn::Expr) -> SynResult<Vec<Ident>> {
+        let fields = self.field_idents();
+        let position = fields.iter().position(|field| field == ident).unwrap_or(0);
+        let mut used = Vec::new();
+
+        for referenced in expr_idents(expr) {
+            match fields.iter().position(|field| *field == referenced) {
+                Some(i) if i >= position => {
+                    return Err(Error::new(
+                        referenced.span(),
+                        format!(
+                            "`{}` depends on `{}`, which is not parsed yet",
+                            ident, referenced
+                        ),
+                    ))
+                }
+                Some(_) if !used.contains(&referenced) => used.push(referenced),
+                _ => {}
+            }
+        }
+
+        Ok(used)
+    }
+
+    /// Evaluates `value`, spelled out by `expr`, over copies of the fields it
+    /// reads, the writer only has references to them. The copies stay inside
+    /// the block so later uses still see the references.
+    fn gen_field_expr(
+        &self,
+        ident: &Ident,
+        expr: &syn::Expr,
+        value: proc_macro2::TokenStream,
+    ) -> SynResult<proc_macro2::TokenStream> {
+        let fields = self.earlier_fields(ident, expr)?;
+        let borrowed = fields.clone();
+
+        Ok(quote! {
+            {
+                #(let #fields = ::std::clone::Clone::clone(#borrowed);)*
+                #value
+            }
+        })
+    }
+
+    /// The length prefix of a field, either its own or the default the type
+    /// sets for its `Vec` and `String` fields. Padded fields fill their
+    /// window instead of taking the default.
+    fn field_length<'b>(&'b self, args: &'b [SnomArg], ty: &syn::Type) -> Option<&'b ConfigArg> {
+        find_length(args).or_else(|| {
+            if is_collection(ty) && !fills_window(args, ty) {
+                find_length(self.type_args)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn gen_field_parser(
+        &self,
+        ident: &Ident,
+        field: &syn::Field,
+    ) -> SynResult<proc_macro2::TokenStream> {
+        let snom_args = parse_args(&field.attrs)?;
+
+        if self.disc_field.as_ref() == Some(ident) {
+            return Ok(quote_spanned!(ident.span()=> let #ident = __snom_disc;));
+        }
+
+        let cond = match find_cond(&snom_args) {
+            Some(cond) => cond,
+            None => return self.gen_value_parser(ident, field),
+        };
+
+        self.earlier_fields(ident, cond)?;
+
+        // The rest of the arguments describe the value inside the `Option`.
+        let inner = syn::Field {
+            ty: option_inner(&field.ty, cond)?.clone(),
+            ..field.clone()
+        };
+        let parser = self.gen_value_parser(ident, &inner)?;
+        let field_span = ident.span();
+
+        Ok(quote_spanned! {field_span=>
+            let (__snom_input, #ident) = if #cond {
+                #parser
+                (__snom_input, Some(#ident))
+            } else {
+                (__snom_input, None)
+            };
+        })
+    }
+
+    fn gen_value_parser(
+        &self,
+        ident: &Ident,
+        field: &syn::Field,
+    ) -> SynResult<proc_macro2::TokenStream> {
+        let snom_args = parse_args(&field.attrs)?;
+
+        if let Some(arg) = snom_args.iter().find_map(SnomArg::match_arg) {
+            return Err(Error::new(
+                arg.span(),
+                "discriminants only apply to enum variants",
+            ));
+        }
+
+        // Fields holding the discriminant never get here.
+        if let Some(arg) = find_discriminant(&snom_args) {
+            return Err(Error::new(
+                arg.span(),
+                "only fields of enum variants can hold the discriminant",
+            ));
+        }
+
+        let value_arg = snom_args.iter().find_map(SnomArg::value_arg);
+        let effect_args: Vec<_> = snom_args.iter().filter_map(SnomArg::effect_arg).collect();
+
+        if let (Some(_), Some(length)) = (value_arg, find_length(&snom_args)) {
+            return Err(Error::new(
+                length.span(),
+                "a length or count cannot be combined with another parser",
+            ));
+        }
+
+        match (value_arg, find_keep_terminator(&snom_args)) {
+            (Some(ValueArg::Until { .. }), _) | (_, None) => {}
+            (_, Some(keep)) => {
+                return Err(Error::new(
+                    keep.span(),
+                    "`keep_terminator` needs an `until` terminator",
+                ))
+            }
+        }
+
+        if let Some(ctx) = snom_args
+            .iter()
+            .filter_map(SnomArg::config_arg)
+            .find(|arg| matches!(arg, ConfigArg::Ctx { .. }))
+        {
+            return Err(Error::new(
+                ctx.span(),
+                "`ctx` applies to types, fields pass one with `with_ctx`",
+            ));
+        }
+
+        if let (Some(arg), Some(_)) = (value_arg, find_with_ctx(&snom_args)) {
+            return Err(Error::new(
+                arg.span(),
+                "`with_ctx` cannot be combined with another parser",
+            ));
+        }
+
+        if let (Some(length), Some(_)) = (find_length(&snom_args), find_with_ctx(&snom_args)) {
+            return Err(Error::new(
+                length.span(),
+                "`with_ctx` cannot be combined with a length or count",
+            ));
+        }
+
+        if let (Some(_), Some(nonzero)) = (value_arg, find_nonzero(&snom_args)) {
+            return Err(Error::new(
+                nonzero.span(),
+                "`nonzero` only applies to plain `bool` fields",
+            ));
+        }
+
+        if let (None, Some(padding)) = (find_size(&snom_args), find_padding(&snom_args)) {
+            return Err(Error::new(
+                padding.span(),
+                "`padding` needs a `size` to pad",
+            ));
+        }
+
+        let encoding = find_encoding(&snom_args);
+
+        if let (Some(arg), Some(_)) = (value_arg, encoding) {
+            if !is_encodable(arg) {
+                return Err(Error::new(
+                    arg.span(),
+                    "`encoding` cannot be combined with this parser",
+                ));
+            }
+        }
+
+        // Padding is stripped from the end of the window, it must not eat
+        // into the last code unit of the text.
+        if let (Some(encoding), Some(ConfigArg::Padding { value, .. })) =
+            (encoding, find_padding(&snom_args))
+        {
+            if value.value().len() % encoding.unit_size() != 0 {
+                return Err(Error::new(
+                    value.span(),
+                    format!(
+                        "`padding` must be a whole number of {} byte code units",
+                        encoding.unit_size()
+                    ),
+                ));
+            }
+        }
+
+        // Encoded strings are parsed as the `Vec` of their code units.
+        let ty = match encoding {
+            Some(encoding) => quote! { Vec<<#encoding as ::structnom::encoding::Encoding>::Unit> },
+            None => {
+                let ty = &field.ty;
+                quote! { #ty }
+            }
+        };
+        let field_span = ident.span();
+
+        let parser = match value_arg {
+            Some(ValueArg::Parser { value, .. }) => {
+                quote_spanned!(field_span=> let (__snom_input, #ident) = #value(__snom_input)?;)
+            }
+            Some(ValueArg::Skip { .. }) => {
+                quote_spanned!(field_span=> let #ident = Default::default();)
+            }
+            Some(ValueArg::Bits { count, .. }) => {
+                let count = usize_lit(count);
+
+                quote_spanned! {field_span=>
+                    let (__snom_input, #ident): (_, #ty) = ::structnom::nom::bits::bits::<_, _, ::structnom::nom::error::Error<(&[u8], usize)>, _, _>(
+                        ::structnom::nom::bits::complete::take(#count),
+                    )(__snom_input)?;
+                }
+            }
+            Some(ValueArg::TagBits { count, pattern, .. }) => {
+                let count = usize_lit(count);
+
+                quote_spanned! {field_span=>
+                    let (__snom_input, #ident): (_, #ty) = ::structnom::nom::bits::bits::<_, _, ::structnom::nom::error::Error<(&[u8], usize)>, _, _>(
+                        ::structnom::nom::combinator::verify(
+                            ::structnom::nom::bits::complete::take(#count),
+                            |value: &#ty| match *value {
+                                #pattern => true,
+                                _ => false,
+                            },
+                        ),
+                    )(__snom_input)?;
+                }
+            }
+            Some(ValueArg::Iter { .. }) => {
+                quote_spanned! {field_span=>
+                    let (__snom_input, #ident) = <#ty as ::structnom::StructNomCount>::nom_rest(__snom_input, __snom_endian, __snom_runtime)?;
+                }
+            }
+            Some(ValueArg::Until { value, .. }) => {
+                let keep = find_keep_terminator(&snom_args).is_some();
+
+                match value {
+                    Terminator::Bytes(bytes) => quote_spanned! {field_span=>
+                        let (__snom_input, #ident) =
+                            <#ty as ::structnom::StructNomCount>::nom_until(__snom_input, #bytes, #keep, __snom_endian, __snom_runtime)?;
+                    },
+                    Terminator::Pattern(pattern) => quote_spanned! {field_span=>
+                        let (__snom_input, #ident) = <#ty as ::structnom::StructNomCount>::nom_until_item(
+                            __snom_input,
+                            |item| matches!(item, #pattern),
+                            #keep,
+                            __snom_endian,
+                            __snom_runtime,
+                        )?;
+                    },
+                }
+            }
+            Some(arg @ ValueArg::Switch { .. }) => {
+                return Err(Error::new(arg.span(), "`switch` only applies to enums"))
+            }
+            None => {
+                // println!("Effect Args: {:?}", effect_args);
+
+                let effect_args = effect_args
+                    .iter()
+                    .map(|effect| effect.parser())
+                    .collect::<SynResult<Vec<_>>>()?;
+
+                let value = match self.field_length(&snom_args, &field.ty) {
+                    Some(ConfigArg::Count { value, .. }) => {
+                        self.earlier_fields(ident, value)?;
+                        let count = gen_usize(value);
+
+                        quote_spanned! {field_span=>
+                            let (__snom_input, #ident) =
+                                <#ty as ::structnom::StructNomCount>::nom_count(__snom_input, #count, __snom_endian, __snom_runtime)?;
+                        }
+                    }
+                    Some(length) => {
+                        let length = gen_length_parser(length);
+
+                        quote_spanned! {field_span=>
+                            let (__snom_input, #ident) = {
+                                #length
+                                <#ty as ::structnom::StructNomCount>::nom_count(
+                                    __snom_input,
+                                    ::structnom::nom::ToUsize::to_usize(&__snom_length),
+                                    __snom_endian,
+                                    __snom_runtime,
+                                )?
+                            };
+                        }
+                    }
+                    // A padded or borrowed field fills whatever its window leaves.
+                    None if fills_window(&snom_args, &field.ty) => quote_spanned! {field_span=>
+                        let (__snom_input, #ident) = <#ty as ::structnom::StructNomCount>::nom_rest(__snom_input, __snom_endian, __snom_runtime)?;
+                    },
+                    // Any byte but zero is `true`, not just the `1` a `bool` insists on.
+                    None if find_nonzero(&snom_args).is_some() => quote_spanned! {field_span=>
+                        let (__snom_input, #ident) = {
+                            let (__snom_input, __snom_byte) = <u8 as ::structnom::StructNom>::nom_endian(__snom_input, __snom_endian)?;
+                            (__snom_input, __snom_byte != 0)
+                        };
+                    },
+                    None => match (find_with_ctx(&snom_args), byte_array_len(&field.ty)) {
+                        (Some(ctx), _) => {
+                            self.earlier_fields(ident, ctx)?;
+
+                            quote_spanned! {field_span=>
+                                let (__snom_input, #ident) = <#ty as ::structnom::StructNomWith<_>>::nom_with(__snom_input, #ctx, __snom_endian, __snom_runtime)?;
+                            }
+                        }
+                        (None, Some(len)) => {
+                            let take = gen_usize(len);
+
+                            quote_spanned! {field_span=>
+                            let (__snom_input, #ident) = {
+                                let (__snom_input, __snom_bytes) = ::structnom::nom::bytes::complete::take(#take)(__snom_input)?;
+                                let mut __snom_array = [0u8; #len];
+
+                                __snom_array.copy_from_slice(__snom_bytes);
+                                (__snom_input, __snom_array)
+                            };
+                            }
+                        }
+                        (None, None) => quote_spanned! {field_span=>
+                            let (__snom_input, #ident) = <#ty as ::structnom::StructNom>::nom_field(__snom_input, __snom_endian, __snom_runtime)?;
+                        },
+                    },
+                };
+
+                quote_spanned! {field_span=>
+                    #(let (__snom_input, _) = #effect_args(__snom_input)?;)*
+                    #value
+                }
+            }
+        };
+
+        let parser = match encoding {
+            Some(encoding) => quote_spanned! {field_span=>
+                let (__snom_input, #ident) = {
+                    let __snom_start = __snom_input;
+                    #parser
+
+                    match <#encoding as ::structnom::encoding::Encoding>::decode(&#ident) {
+                        Some(__snom_text) => (__snom_input, __snom_text),
+                        None => {
+                            return Err(::structnom::nom::Err::Error(::structnom::nom::error::make_error(
+                                __snom_start,
+                                ::structnom::nom::error::ErrorKind::Verify,
+                            )))
+                        }
+                    }
+                };
+            },
+            None => parser,
+        };
+
+        // A field's own byte order applies to everything it parses, length
+        // prefixes included, but not to the fields after it.
+        let parser = match find_endian(&snom_args) {
+            Some(endian) => quote_spanned! {field_span=>
+                let (__snom_input, #ident) = {
+                    let __snom_endian = #endian;
+                    #parser
+                    (__snom_input, #ident)
+                };
+            },
+            None => parser,
+        };
+
+        let size = match find_size(&snom_args) {
+            Some(ConfigArg::Size { value, .. }) => value,
+            _ => {
+                if let Some(complete) = find_complete(&snom_args) {
+                    return Err(Error::new(
+                        complete.span(),
+                        "`complete` needs a `size` to check",
+                    ));
+                }
+
+                return Ok(parser);
+            }
+        };
+
+        self.earlier_fields(ident, size)?;
+        let size = gen_usize(size);
+
+        let padding = find_padding(&snom_args).map(|padding| match padding {
+            ConfigArg::Padding { value, .. } => quote! {
+                let mut __snom_input = __snom_input;
+
+                while __snom_input.ends_with(#value) {
+                    __snom_input = &__snom_input[..__snom_input.len() - (#value).len()];
+                }
+            },
+            _ => quote! {},
+        });
+
+        // The field sees only its window as input, whatever it leaves over
+        // is either skipped or, with `complete`, an error.
+        let complete = find_complete(&snom_args).map(|_| {
+            quote! {
+                if !__snom_input.is_empty() {
+                    return Err(::structnom::nom::Err::Error(::structnom::nom::error::make_error(
+                        __snom_input,
+                        ::structnom::nom::error::ErrorKind::Eof,
+                    )));
+                }
+            }
+        });
+
+        Ok(quote_spanned! {field_span=>
+            let (__snom_input, #ident) = {
+                let (__snom_rest, __snom_input) = ::structnom::nom::bytes::complete::take(#size)(__snom_input)?;
+                #padding
+                #parser
+                #complete
+                (__snom_rest, #ident)
+            };
+        })
+    }
+
+    /// The pattern binding every field of `self`, using the same idents as
+    /// the generated parser.
+    pub fn gen_pattern(&self) -> proc_macro2::TokenStream {
+        let name = self.gen_name();
+
+        match &self.fields {
+            Fields::Named(named) => {
+                let idents = named.named.iter().map(|f| &f.ident);
+
+                quote! { #name { #(#idents),* } }
+            }
+            Fields::Unnamed(unnamed) => {
+                let idents = unnamed
+                    .unnamed
+                    .iter()
+                    .enumerate()
+                    .map(|(i, f)| Ident::new(&format!("f_{}", i), f.ident.span()));
+
+                quote! { #name ( #(#idents),* ) }
+            }
+            Fields::Unit => quote! { #name },
+        }
+    }
+
+    /// Writes every field bound by `gen_pattern`. A field whose parser
+    /// cannot be inverted is an error pointing at that parser.
+    pub fn gen_writer(&self) -> SynResult<proc_macro2::TokenStream> {
+        let mut writers = Vec::new();
+        let count = self.fields.iter().count();
+
+        for (i, (field_ident, field)) in self
+            .field_idents()
+            .into_iter()
+            .zip(self.fields.iter())
+            .enumerate()
+        {
+            if i + 1 < count {
+                check_bare_option(field)?;
+            }
+
+            writers.push(self.gen_field_writer(&field_ident, field)?);
+        }
+
+        Ok(quote! { #(#writers)* })
+    }
+
+    pub fn gen_field_writer(
+        &self,
+        ident: &Ident,
+        field: &syn::Field,
+    ) -> SynResult<proc_macro2::TokenStream> {
+        let snom_args = parse_args(&field.attrs)?;
+
+        if self.disc_field.as_ref() == Some(ident) {
+            return Ok(quote! {});
+        }
+
+        let cond = match find_cond(&snom_args) {
+            Some(cond) => cond,
+            None => return self.gen_value_writer(ident, field),
+        };
+
+        let inner = syn::Field {
+            ty: option_inner(&field.ty, cond)?.clone(),
+            ..field.clone()
+        };
+        let writer = self.gen_value_writer(ident, &inner)?;
+        let cond = self.gen_field_expr(ident, cond, quote!(#cond))?;
+        let error = format!("`{}` must be present exactly when its `cond` holds", ident);
+        let field_span = ident.span();
+
+        Ok(quote_spanned! {field_span=>
+            match (#cond, #ident) {
+                (true, Some(#ident)) => {
+                    #writer
+                }
+                (false, None) => {}
+                _ => return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, #error)),
+            }
+        })
+    }
+
+    fn gen_value_writer(
+        &self,
+        ident: &Ident,
+        field: &syn::Field,
+    ) -> SynResult<proc_macro2::TokenStream> {
+        let snom_args = parse_args(&field.attrs)?;
+
+        let value_arg = snom_args.iter().find_map(SnomArg::value_arg);
+        let write_arg = snom_args.iter().find_map(SnomArg::write_arg);
+        let effect_args: Vec<_> = snom_args.iter().filter_map(SnomArg::effect_arg).collect();
+
+        let field_span = ident.span();
+
+        let writer = match (value_arg, write_arg) {
+            (Some(ValueArg::Skip { .. }), _) => quote_spanned!(field_span=> let _ = #ident;),
+            (Some(_), Some(WriteArg::Writer { value, .. })) => {
+                quote_spanned!(field_span=> #value(#ident, __snom_out)?;)
+            }
+            (Some(ValueArg::Bits { count, .. }), _)
+            | (Some(ValueArg::TagBits { count, .. }), _) => {
+                // The parser reads whole bytes, the value lives in the most
+                // significant `count` bits of them.
+                let bytes = count.value().div_ceil(8);
+                let shift = proc_macro2::Literal::u64_unsuffixed(bytes * 8 - count.value());
+                let start = proc_macro2::Literal::u64_unsuffixed(16 - bytes);
+                let error = format!("`{}` does not fit in {} bits", ident, count.value());
+
+                // Every `u128` fits in 128 bits, and shifting by as many
+                // would overflow.
+                let check = if count.value() < 128 {
+                    let count = proc_macro2::Literal::u64_unsuffixed(count.value());
+
+                    quote! {
+                        if __snom_bits >> #count != 0 {
+                            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, #error));
+                        }
+                    }
+                } else {
+                    quote! {}
+                };
+
+                quote_spanned! {field_span=>
+                    {
+                        #[allow(clippy::unnecessary_cast)]
+                        let __snom_bits = *#ident as u128;
+                        #check
+                        __snom_out.write_all(&(__snom_bits << #shift).to_be_bytes()[#start..])?;
+                    }
+                }
+            }
+            (Some(ValueArg::Iter { .. }), _) => quote_spanned! {field_span=>
+                ::structnom::StructNomWriteCount::write_count(#ident, __snom_out, __snom_endian, __snom_runtime)?;
+            },
+            (Some(ValueArg::Until { value, .. }), _) => {
+                let keep = find_keep_terminator(&snom_args).is_some();
+
+                // A kept byte terminator is written by whatever parses it
+                // next, a kept element is part of the field already.
+                let terminator = match (value, keep) {
+                    (_, true) => quote! {},
+                    (Terminator::Bytes(bytes), false) => quote! { __snom_out.write_all(#bytes)?; },
+                    (Terminator::Pattern(_), false) => {
+                        return Err(Error::new(
+                            value_arg.map_or(field_span, ValueArg::span),
+                            "`until` drops the element matching its pattern, which cannot be written back without `keep_terminator`",
+                        ))
+                    }
+                };
+
+                quote_spanned! {field_span=>
+                    ::structnom::StructNomWriteCount::write_count(#ident, __snom_out, __snom_endian, __snom_runtime)?;
+                    #terminator
+                }
+            }
+            (Some(arg @ ValueArg::Parser { .. }), None) => return Err(parser_without_writer(arg)),
+            (Some(arg), None) => {
+                return Err(Error::new(arg.span(), "`switch` only applies to enums"))
+            }
+            (None, write_arg) => {
+                let effects = effect_args
+                    .iter()
+                    .map(|effect| effect.writer())
+                    .collect::<SynResult<Vec<_>>>()?;
+
+                let value = match (write_arg, self.field_length(&snom_args, &field.ty)) {
+                    (Some(WriteArg::Writer { value, .. }), _) => {
+                        quote_spanned!(field_span=> #value(#ident, __snom_out)?;)
+                    }
+                    (None, Some(ConfigArg::Length { value, .. })) => {
+                        let error = format!(
+                            "`{}` does not fit in its `{}` length prefix",
+                            ident,
+                            quote!(#value)
+                        );
+
+                        quote_spanned! {field_span=>
+                            {
+                                let __snom_length: #value = ::std::convert::TryFrom::try_from(
+                                    ::structnom::StructNomWriteCount::count(#ident),
+                                )
+                                .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, #error))?;
+
+                                ::structnom::StructNomWrite::write_endian(&__snom_length, __snom_out, __snom_endian)?;
+                                ::structnom::StructNomWriteCount::write_count(#ident, __snom_out, __snom_endian, __snom_runtime)?;
+                            }
+                        }
+                    }
+                    (None, Some(ConfigArg::Count { value, .. })) => {
+                        let count = self.gen_field_expr(ident, value, gen_usize(value))?;
+                        let error =
+                            format!("`{}` does not hold as many elements as its count", ident);
+
+                        quote_spanned! {field_span=>
+                            if #count != ::structnom::StructNomWriteCount::count(#ident) {
+                                return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, #error));
+                            }
+
+                            ::structnom::StructNomWriteCount::write_count(#ident, __snom_out, __snom_endian, __snom_runtime)?;
+                        }
+                    }
+                    (None, Some(length @ ConfigArg::LengthParser { .. })) => {
+                        // The writer sits next to the `length_parser` it
+                        // mirrors, on the field or on the type.
+                        let length_writer = if find_length(&snom_args).is_some() {
+                            find_length_writer(&snom_args)
+                        } else {
+                            find_length_writer(self.type_args)
+                        };
+
+                        let length_writer = length_writer.ok_or_else(|| {
+                            Error::new(
+                                length.span(),
+                                "`length_parser` cannot be written back without a matching `#[snom(length_writer = path)]`",
+                            )
+                        })?;
+
+                        quote_spanned! {field_span=>
+                            #length_writer(::structnom::StructNomWriteCount::count(#ident), __snom_out)?;
+                            ::structnom::StructNomWriteCount::write_count(#ident, __snom_out, __snom_endian, __snom_runtime)?;
+                        }
+                    }
+                    (None, Some(_)) => unreachable!("`find_length` only returns length arguments"),
+                    (None, None) if fills_window(&snom_args, &field.ty) => {
+                        quote_spanned! {field_span=>
+                            ::structnom::StructNomWriteCount::write_count(#ident, __snom_out, __snom_endian, __snom_runtime)?;
+                        }
+                    }
+                    (None, None) => match (find_with_ctx(&snom_args), byte_array_len(&field.ty)) {
+                        (Some(ctx), _) => {
+                            let ctx = self.gen_field_expr(ident, ctx, quote!(#ctx))?;
+
+                            quote_spanned! {field_span=>
+                                ::structnom::StructNomWriteWith::write_with(#ident, __snom_out, #ctx, __snom_endian, __snom_runtime)?;
+                            }
+                        }
+                        (None, Some(_)) => {
+                            quote_spanned!(field_span=> __snom_out.write_all(#ident)?;)
+                        }
+                        (None, None) => {
+                            quote_spanned!(field_span=> ::structnom::StructNomWrite::write_field(#ident, __snom_out, __snom_endian, __snom_runtime)?;)
+                        }
+                    },
+                };
+
+                quote! {
+                    #(#effects)*
+                    #value
+                }
+            }
+        };
+
+        let writer = match find_endian(&snom_args) {
+            Some(endian) => quote_spanned! {field_span=>
+                {
+                    let __snom_endian = #endian;
+                    #writer
+                }
+            },
+            None => writer,
+        };
+
+        let writer = match (find_encoding(&snom_args), write_arg) {
+            (Some(encoding), None) => {
+                let error = format!("`{}` cannot be encoded as {:?}", ident, encoding);
+
+                quote_spanned! {field_span=>
+                    {
+                        let #ident = &match <#encoding as ::structnom::encoding::Encoding>::encode(#ident) {
+                            Some(units) => units,
+                            None => return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, #error)),
+                        };
+
+                        #writer
+                    }
+                }
+            }
+            _ => writer,
+        };
+
+        let size = match find_size(&snom_args) {
+            Some(ConfigArg::Size { value, .. }) => value,
+            _ => return Ok(writer),
+        };
+
+        let size = self.gen_field_expr(ident, size, gen_usize(size))?;
+        let fill = match find_padding(&snom_args) {
+            Some(ConfigArg::Padding { value, .. }) => quote! {
+                let __snom_fill: Vec<u8> = (#value).iter().copied().cycle().take(__snom_size - __snom_window.len()).collect();
+                __snom_out.write_all(&__snom_fill)?;
+            },
+            _ => quote! { __snom_out.write_all(&vec![0u8; __snom_size - __snom_window.len()])?; },
+        };
+        let too_long = match find_complete(&snom_args) {
+            Some(_) => quote! { __snom_window.len() != __snom_size },
+            None => quote! { __snom_window.len() > __snom_size },
+        };
+        let error = format!("`{}` does not fit its size", ident);
+
+        // Fields are written to a buffer first to check them against their
+        // size, a window the parser skips the rest of is padded with zeros.
+        Ok(quote_spanned! {field_span=>
+            {
+                #[allow(unused_imports)]
+                use std::io::Write as _;
+
+                let __snom_size = #size;
+                let mut __snom_window = Vec::new();
+
+                {
+                    let __snom_out = &mut __snom_window;
+                    #writer
+                }
+
+                if #too_long {
+                    return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, #error));
+                }
+
+                __snom_out.write_all(&__snom_window)?;
+                #fill
+            }
+        })
+    }
+
+    fn gen_unit_parser(&self) -> SynResult<proc_macro2::TokenStream> {
+        let name = self.gen_name();
+
+        Ok(quote! { Ok((__snom_input, #name)) })
+    }
+
+    fn gen_name(&self) -> proc_macro2::TokenStream {
+        if let Some(ref ident) = self.variant {
+            let name = self.name;
+            quote! { #name::#ident }
+        } else {
+            let name = self.name;
+            quote! { #name }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::{parse2, Data};
+
+    fn get_enum_data(
+        code: proc_macro2::TokenStream,
+    ) -> (Ident, Vec<Attribute>, Generics, DataEnum) {
+        let input = parse2::<syn::DeriveInput>(code).unwrap();
+
+        let name = &input.ident;
+        let attrs = input.attrs;
+        let generics = input.generics;
+
+        if let Data::Enum(data) = input.data {
+            (name.clone(), attrs.clone(), generics, data)
+        } else {
+            panic!("Not an enum")
+        }
+    }
+
+    fn get_struct_data(
+        code: proc_macro2::TokenStream,
+    ) -> (Ident, Vec<Attribute>, Generics, DataStruct) {
+        let input = parse2::<syn::DeriveInput>(code).unwrap();
+
+        let name = &input.ident;
+        let attrs = input.attrs;
+        let generics = input.generics;
+
+        if let Data::Struct(data) = input.data {
+            (name.clone(), attrs.clone(), generics, data)
+        } else {
+            panic!("Not an enum")
+        }
+    }
+
+    #[test]
+    fn enum_gen() {
+        // let code: proc_macro2::TokenStream = syn::parse_quote! {
+        //     #[derive(StructNom)]
+        //     #[snom(switch = le_u8)]
+        //     pub enum MyEnum {
+        //         #[snom(values(0x04, 0x03, 5, 10))]
+        //         First { thing_one: u32, memes: Vec<u8> },
+        //         #[snom(val = 0x04)]
+        //         Unnamed(u64, Expr),
+        //     }
+        // };
+
+        let code: proc_macro2::TokenStream = syn::parse_quote! {
+            #[derive(StructNom)]
+            #[snom(switch = le_u8)]
+            pub enum Instr {
+                #[snom(range(start = 1))]
+                Nop, // 1
+                If,  // 2
+                #[snom(parser = crate::my_parser)]
+                Add, // 3
+                #[snom(range(skip))]
+                Sub, // 5
+                #[snom(val = 0x04)]
+                Mul, //
+                #[snom(range(skip = 3))]
+                Skip3, // 9
+                #[snom(range(end = 10))]
+                Equal, // 10
+                #[snom(val = 0x0F)]
+                Another, // 15
+            }
+        };
+
+        let (name, attrs, generics, data) = get_enum_data(code);
+
+        let mut gen = EnumGen::new(name, attrs, generics, data).unwrap();
+
+        let enum_impl = gen.gen_impl().unwrap();
+
+        // println!("{}", enum_impl);
+
+        parse2::<syn::ItemImpl>(enum_impl).unwrap();
+    }
+
+    #[test]
+    fn duplicate_discriminants() {
+        let cases: Vec<proc_macro2::TokenStream> = vec![
+            syn::parse_quote! {
+                #[snom(switch = le_u8)]
+                pub enum Instr {
+                    #[snom(range(start = 1))]
+                    Nop, // 1
+                    If,  // 2
+                    #[snom(val = 2)]
+                    Mul,
+                }
+            },
+            syn::parse_quote! {
+                #[snom(switch = le_u8)]
+                pub enum Instr {
+                    #[snom(values(4, 8))]
+                    Load,
+                    #[snom(range(start = 6))]
+                    Nop, // 6
+                    #[snom(range(skip))]
+                    If,  // 8
+                }
+            },
+            syn::parse_quote! {
+                #[snom(switch = le_u8)]
+                pub enum Instr {
+                    #[snom(range = 0x10..0x20)]
+                    Load,
+                    #[snom(values(0x30, 0x1F))]
+                    Store,
+                }
+            },
+        ];
+
+        for code in cases {
+            let (name, attrs, generics, data) = get_enum_data(code);
+            let mut gen = EnumGen::new(name, attrs, generics, data).unwrap();
+
+            assert!(gen.gen_impl().is_err());
+        }
+    }
+
+    #[test]
+    fn discriminant_overflow() {
+        let code = syn::parse_quote! {
+            #[snom(switch = le_u64)]
+            pub enum Instr {
+                #[snom(range(start = 0xFFFF_FFFF_FFFF_FFFF))]
+                Last,
+                Past,
+            }
+        };
+
+        let (name, attrs, generics, data) = get_enum_data(code);
+        let mut gen = EnumGen::new(name, attrs, generics, data).unwrap();
+
+        assert!(gen.gen_impl().is_err());
+
+        let code = syn::parse_quote! {
+            #[repr(u64)]
+            pub enum Wide {
+                Zero,
+                Max = 0xFFFF_FFFF_FFFF_FFFF,
+            }
+        };
+
+        let (name, attrs, generics, data) = get_enum_data(code);
+        let mut gen = EnumGen::new(name, attrs, generics, data).unwrap();
+
+        assert!(gen.gen_impl().is_ok());
+    }
+
+    #[test]
+    fn struct_gen() {
+        let code: proc_macro2::TokenStream = syn::parse_quote! {
+            #[derive(StructNom, StructNomWrite)]
+            pub struct MyStruct(#[snom(tag(SOME_SLICE))] u32, Expr);
+        };
+
+        let (name, attrs, generics, data) = get_struct_data(code);
+
+        let mut gen = StructGen::new(name, attrs, generics, data).unwrap();
+        let struct_impl = gen.gen_impl().unwrap();
+
+        // println!("{}", struct_impl);
+
+        parse2::<syn::ItemImpl>(struct_impl).unwrap();
+    }
+
+    #[test]
+    fn struct_write_gen() {
+        let code: proc_macro2::TokenStream = syn::parse_quote! {
+            #[derive(StructNom, StructNomWrite)]
+            pub struct MyStruct(#[snom(tag(SOME_SLICE))] u32, Expr);
+        };
+
+        let (name, attrs, generics, data) = get_struct_data(code);
+        let write_impl = StructGen::new(name, attrs, generics, data)
+            .unwrap()
+            .gen_write_impl()
+            .unwrap();
+
+        assert!(write_impl.to_string().contains("StructNomWrite"));
+
+        let code: proc_macro2::TokenStream = syn::parse_quote! {
+            #[derive(StructNomWrite)]
+            pub struct MyStruct {
+                #[snom(call(le_u32))]
+                first: u32,
+            }
+        };
+
+        let (name, attrs, generics, data) = get_struct_data(code);
+        let write_impl = StructGen::new(name, attrs, generics, data)
+            .unwrap()
+            .gen_write_impl();
+
+        assert!(write_impl.is_err());
+    }
+}
+
+// pub fn gen_enum_field(field: &Field, variant: &Variant, state: &mut EnumState) -> proc_macro2::TokenStream {
+//     unimplemented!()
+// }
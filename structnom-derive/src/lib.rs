@@ -0,0 +1,78 @@
+#![recursion_limit = "128"]
+
+extern crate proc_macro;
+
+use crate::proc_macro::TokenStream;
+
+use syn::{parse_macro_input, Data, DeriveInput};
+
+mod attr;
+mod gen;
+
+use gen::*;
+
+#[proc_macro_derive(StructNom, attributes(snom))]
+pub fn nom_derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match expand(input, Derive::Nom) {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(error) => TokenStream::from(error.to_compile_error()),
+    }
+}
+
+/// Writing is opt-in, not every type that can be parsed can be written back.
+#[proc_macro_derive(StructNomWrite, attributes(snom))]
+pub fn write_derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match expand(input, Derive::Write) {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(error) => TokenStream::from(error.to_compile_error()),
+    }
+}
+
+/// The trait a derive implements.
+#[derive(Debug, Clone, Copy)]
+enum Derive {
+    Nom,
+    Write,
+}
+
+impl Derive {
+    fn name(self) -> &'static str {
+        match self {
+            Derive::Nom => "StructNom",
+            Derive::Write => "StructNomWrite",
+        }
+    }
+}
+
+fn expand(input: DeriveInput, derive: Derive) -> syn::Result<proc_macro2::TokenStream> {
+    let name = input.ident;
+    // let attrs = &input.attrs;
+    let generics = input.generics;
+
+    match input.data {
+        Data::Struct(data) => {
+            let mut gen = StructGen::new(name, input.attrs, generics, data)?;
+
+            match derive {
+                Derive::Nom => gen.gen_impl(),
+                Derive::Write => gen.gen_write_impl(),
+            }
+        }
+        Data::Enum(data) => {
+            let mut generator = EnumGen::new(name, input.attrs, generics, data)?;
+
+            match derive {
+                Derive::Nom => generator.gen_impl(),
+                Derive::Write => generator.gen_write_impl(),
+            }
+        }
+        Data::Union(data) => Err(syn::Error::new(
+            data.union_token.span,
+            format!("{} cannot be derived for unions", derive.name()),
+        )),
+    }
+}
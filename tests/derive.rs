@@ -0,0 +1,964 @@
+use structnom::nom::number::complete::{be_u16, le_u8};
+use structnom::{Ctx, Endian, StructNom, StructNomWrite};
+
+static MAGIC: &[u8] = &[0xCA, 0xFE];
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+#[snom(switch = le_u8)]
+pub enum Instr {
+    #[snom(range(start = 1))]
+    Nop,
+    If,
+    Add {
+        a: u16,
+        b: Vec<u8>,
+    },
+    #[snom(range(skip))]
+    Sub(u32, i8),
+    #[snom(values(0x20, 0x21))]
+    Many,
+    #[snom(val = 0x0F)]
+    Another,
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+pub struct Fields {
+    #[snom(tag(MAGIC))]
+    first: u32,
+    #[snom(take(2))]
+    #[snom(tag(0x01, 0x02))]
+    last: u64,
+    #[snom(bits(3))]
+    b: u8,
+    #[snom(bits(13))]
+    c: u16,
+    #[snom(bits(4, 0x0A))]
+    d: u8,
+    instrs: Vec<Instr>,
+    #[snom(skip)]
+    skipped: u8,
+    opt: Option<u8>,
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+pub struct WideBits {
+    #[snom(bits(72))]
+    wide: u128,
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+#[snom(endian = big)]
+pub struct Big {
+    value: u32,
+    inner: Inherit,
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+pub struct Inherit(u16);
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+#[snom(length = u16)]
+pub struct Lengths {
+    short: Vec<u8>,
+    #[snom(length = u32)]
+    long: Vec<u16>,
+}
+
+fn write_be_u16_length<W: std::io::Write>(length: usize, out: &mut W) -> std::io::Result<()> {
+    out.write_all(&(length as u16).to_be_bytes())
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+pub struct CustomLength {
+    #[snom(length_parser = be_u16)]
+    #[snom(length_writer = write_be_u16_length)]
+    data: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+pub struct Counted {
+    num: u16,
+    #[snom(count = num)]
+    sections: Vec<Inherit>,
+    #[snom(count = num as usize * 2)]
+    bytes: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+pub struct Windowed {
+    len: u8,
+    #[snom(size = len)]
+    padded: Inherit,
+    #[snom(size = 2)]
+    #[snom(complete)]
+    exact: Inherit,
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+pub struct Exact {
+    len: u8,
+    #[snom(size = len)]
+    #[snom(complete)]
+    value: Inherit,
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+pub struct Repeated {
+    len: u8,
+    #[snom(size = len)]
+    #[snom(iter)]
+    inner: Vec<Inherit>,
+    #[snom(iter)]
+    rest: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+#[snom(switch = le_u8)]
+pub enum Op {
+    #[snom(val = 0)]
+    End,
+    #[snom(val = 1)]
+    Push(u8),
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+pub struct Terminated {
+    #[snom(until = [0x00])]
+    name: Vec<u8>,
+    #[snom(until = Op::End)]
+    #[snom(keep_terminator)]
+    code: Vec<Op>,
+    #[snom(until = b"\r\n")]
+    #[snom(keep_terminator)]
+    line: Vec<u8>,
+    #[snom(tag(0x0D, 0x0A))]
+    last: u8,
+}
+
+#[derive(Debug, PartialEq, StructNom)]
+pub struct DroppedEnd {
+    #[snom(until = Op::End)]
+    code: Vec<Op>,
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+#[snom(length = u16)]
+pub struct Text {
+    prefixed: String,
+    #[snom(until = [0x00])]
+    c_str: String,
+    #[snom(size = 6)]
+    #[snom(padding = b" ")]
+    fixed: String,
+    #[snom(encoding = latin1)]
+    #[snom(until = [0x00])]
+    latin: String,
+    #[snom(encoding = "UTF-16BE")]
+    #[snom(length = u8)]
+    wide: String,
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+pub struct PaddedWide {
+    #[snom(encoding = utf16le)]
+    #[snom(size = 8)]
+    #[snom(padding = [0x00, 0x00])]
+    name: String,
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+pub struct Borrowed<'a> {
+    len: u8,
+    #[snom(size = len)]
+    payload: &'a [u8],
+    #[snom(until = [0x00])]
+    name: &'a str,
+    #[snom(count = 2)]
+    pair: &'a [u8],
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+pub struct Framed<'a, T> {
+    header: T,
+    #[snom(iter)]
+    body: &'a [u8],
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+pub struct Arrays {
+    mac: [u8; 6],
+    regs: [Inherit; 2],
+    #[snom(count = 2)]
+    table: Vec<[u8; 2]>,
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+pub struct Flags {
+    strict: bool,
+    #[snom(nonzero)]
+    lenient: bool,
+    letter: char,
+    pair: (u8, Inherit),
+    marker: std::marker::PhantomData<u64>,
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+pub struct Mixed {
+    little: u16,
+    #[snom(endian = "big")]
+    network: u16,
+    #[snom(endian = big)]
+    #[snom(length = u16)]
+    ports: Vec<u16>,
+    #[snom(endian = native)]
+    host: u32,
+    after: u16,
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+#[snom(switch = le_u8)]
+pub enum Record {
+    #[snom(val = 1)]
+    Le(u16),
+    #[snom(val = 2)]
+    #[snom(endian = big)]
+    Be(u16),
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+#[snom(endian = little)]
+pub struct Runtime {
+    version: u16,
+    #[snom(endian = ctx)]
+    #[snom(length = u16)]
+    body: Vec<u16>,
+    inner: Inherit,
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+#[snom(ctx = (u8, bool))]
+pub struct Versioned {
+    #[snom(count = ctx.0)]
+    items: Vec<u8>,
+    #[snom(size = if ctx.1 { 2usize } else { 0 })]
+    extra: Option<u16>,
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+pub struct Parent {
+    version: u8,
+    flags: u8,
+    #[snom(with_ctx = (version, flags & 1 != 0))]
+    child: Versioned,
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+pub struct Optional {
+    flags: u8,
+    #[snom(cond = flags & 0x1 != 0)]
+    first: Option<u16>,
+    #[snom(cond = flags & 0x2 != 0)]
+    #[snom(length = u8)]
+    second: Option<Vec<u8>>,
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+#[snom(switch = le_u8)]
+pub enum Extended {
+    #[snom(val = 1)]
+    Item(u8, #[snom(cond = f_0 > 1)] Option<u8>),
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+#[snom(selector = u16)]
+pub enum Rdata {
+    #[snom(val = 1)]
+    A([u8; 4]),
+    #[snom(values(16, 99))]
+    Txt(#[snom(iter)] Vec<u8>),
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+pub struct Resource {
+    rtype: u16,
+    #[snom(selector = rtype)]
+    data: Rdata,
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+#[snom(switch = le_u8)]
+pub enum Message {
+    #[snom(val = 1)]
+    Ping(u32),
+    #[snom(default)]
+    Unknown {
+        tag: u8,
+        #[snom(length = u16)]
+        body: Vec<u8>,
+    },
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+#[snom(switch = le_u8)]
+pub enum Opcode {
+    #[snom(values(0x04, 0x03, 5, 10))]
+    Load {
+        #[snom(discriminant)]
+        opcode: u8,
+        slot: u8,
+    },
+    #[snom(range(start = 0x20))]
+    Ret,
+    Call(u16, #[snom(discriminant)] u8),
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+#[snom(switch = le_u8)]
+pub enum Evm {
+    #[snom(val = 0)]
+    Stop,
+    #[snom(range = 0x60..=0x7F)]
+    Push(#[snom(discriminant)] u8),
+    #[snom(range = 0x80..0x88, 0x90..0x98)]
+    Stack,
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+#[repr(u8)]
+pub enum Branch {
+    Nop = 1,
+    If = 2,
+    Else,
+    #[snom(val = 0x10)]
+    End,
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+#[snom(endian = big)]
+#[repr(u16)]
+pub enum Frame {
+    Data(u16),
+    Ping = 0x10,
+    Pong,
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+#[repr(u64)]
+pub enum Wide {
+    Zero = 0,
+    Max = 0xFFFF_FFFF_FFFF_FFFF,
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+#[repr(i8)]
+pub enum Signed {
+    Minus = -2,
+    Plus = 1,
+    Next,
+    Back = -1,
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+#[snom(endian = big)]
+pub enum Chunk {
+    #[snom(magic = b"IHDR")]
+    Header { width: u32, height: u32 },
+    #[snom(magic = b"IEND")]
+    End,
+    #[snom(magic = b"IE")]
+    Short(u8),
+    #[snom(magic = b"\x89PNG")]
+    Signature,
+    #[snom(default)]
+    Unknown {
+        tag: [u8; 4],
+        #[snom(length = u32)]
+        data: Vec<u8>,
+    },
+}
+
+/// Fields named like the locals of the generated parser.
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+pub struct Locals {
+    input: u8,
+    #[snom(size = input)]
+    #[snom(count = input)]
+    rest: Vec<u8>,
+    #[snom(length = u8)]
+    #[snom(encoding = latin1)]
+    start: String,
+    bytes: [u8; 2],
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+#[snom(switch = le_u8)]
+pub enum Shadowed {
+    #[snom(val = 1)]
+    Input { input: u8, disc: u8 },
+    #[snom(default)]
+    Other { disc: u8, start: u8 },
+}
+
+/// A TIFF header, its byte order mark is a field like any other.
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+#[snom(endian = big)]
+pub struct TiffHeader {
+    endian: u16,
+    magic: u16,
+    offset: u32,
+}
+
+/// Fields named like the locals of the generated writer.
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+pub struct Window {
+    size: u8,
+    #[snom(size = size)]
+    #[snom(padding = b" ")]
+    window: String,
+    #[snom(endian = big)]
+    out: u16,
+    #[snom(length = u8)]
+    fill: Vec<u8>,
+}
+
+/// An IFD only learns its byte order from the header of the file around it.
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+pub struct Ifd {
+    #[snom(endian = ctx)]
+    count: u16,
+}
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+#[snom(endian = little)]
+pub struct Tiff {
+    order: [u8; 2],
+    ifd: Ifd,
+    #[snom(length = u8)]
+    more: Vec<Ifd>,
+}
+
+fn roundtrip<T: for<'a> StructNom<'a> + StructNomWrite + PartialEq + std::fmt::Debug>(
+    value: T,
+) -> Vec<u8> {
+    let mut out = Vec::new();
+    value.write(&mut out).unwrap();
+
+    let (rest, parsed) = T::nom(&out).unwrap();
+
+    assert!(rest.is_empty());
+    assert_eq!(parsed, value);
+
+    out
+}
+
+#[test]
+fn enum_roundtrip() {
+    assert_eq!(roundtrip(Instr::Nop), [1]);
+    assert_eq!(roundtrip(Instr::Sub(9, -1)), [5, 9, 0, 0, 0, 0xFF]);
+    assert_eq!(roundtrip(Instr::Many), [0x20]);
+
+    roundtrip(Instr::Add {
+        a: 3,
+        b: vec![1, 2],
+    });
+}
+
+#[test]
+fn enum_values() {
+    assert_eq!(Instr::nom(&[0x21]).unwrap().1, Instr::Many);
+    assert!(Instr::nom(&[0x22]).is_err());
+}
+
+#[test]
+fn struct_roundtrip() {
+    roundtrip(Fields {
+        first: 7,
+        last: 9,
+        b: 5,
+        c: 0x1ABC,
+        d: 0xA,
+        opt: Some(3),
+        instrs: vec![Instr::If, Instr::Another],
+        skipped: 0,
+    });
+
+    // `b` only has 3 bits to be written in.
+    let overflow = Fields {
+        first: 7,
+        last: 9,
+        b: 0xFF,
+        c: 0x1ABC,
+        d: 0xA,
+        opt: Some(3),
+        instrs: Vec::new(),
+        skipped: 0,
+    };
+
+    assert!(overflow.write(&mut Vec::new()).is_err());
+
+    let bytes = roundtrip(WideBits {
+        wide: 0x01_0203_0405_0607_0809,
+    });
+
+    assert_eq!(bytes, [1, 2, 3, 4, 5, 6, 7, 8, 9]);
+}
+
+#[test]
+fn type_endian() {
+    let bytes = roundtrip(Big {
+        value: 0x0102_0304,
+        inner: Inherit(0x0506),
+    });
+
+    assert_eq!(bytes, [1, 2, 3, 4, 5, 6]);
+
+    // Without its own attribute `Inherit` follows the caller.
+    assert_eq!(
+        Inherit::nom_endian(&[5, 6], Endian::Big).unwrap().1,
+        Inherit(0x0506)
+    );
+    assert_eq!(Inherit::nom(&[5, 6]).unwrap().1, Inherit(0x0605));
+}
+
+#[test]
+fn length_prefix() {
+    let bytes = roundtrip(Lengths {
+        short: vec![7],
+        long: vec![1, 2],
+    });
+
+    assert_eq!(bytes, [1, 0, 7, 2, 0, 0, 0, 1, 0, 2, 0]);
+
+    let (rest, custom) = CustomLength::nom(&[0, 2, 9, 8, 7]).unwrap();
+
+    assert_eq!(rest, [7]);
+    assert_eq!(custom.data, [9, 8]);
+
+    let bytes = roundtrip(CustomLength { data: vec![9, 8] });
+
+    assert_eq!(bytes, [0, 2, 9, 8]);
+}
+
+#[test]
+fn count_from_field() {
+    let bytes = roundtrip(Counted {
+        num: 1,
+        sections: vec![Inherit(0x0102)],
+        bytes: vec![3, 4],
+    });
+
+    assert_eq!(bytes, [1, 0, 2, 1, 3, 4]);
+
+    let mismatched = Counted {
+        num: 2,
+        sections: vec![],
+        bytes: vec![],
+    };
+
+    assert!(mismatched.write(&mut Vec::new()).is_err());
+}
+
+#[test]
+fn size_window() {
+    let bytes = roundtrip(Windowed {
+        len: 4,
+        padded: Inherit(0x0102),
+        exact: Inherit(0x0304),
+    });
+
+    assert_eq!(bytes, [4, 2, 1, 0, 0, 4, 3]);
+
+    // Too short a window for its field.
+    assert!(Windowed::nom(&[1, 2, 1, 4, 3]).is_err());
+
+    // Input after the last window is left to the caller.
+    let (rest, _) = Windowed::nom(&[2, 2, 1, 4, 3, 0]).unwrap();
+    assert_eq!(rest, [0]);
+
+    // Left over bytes in a window are skipped, unless it is `complete`.
+    let (rest, windowed) = Windowed::nom(&[3, 2, 1, 9, 4, 3]).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(windowed.padded, Inherit(0x0102));
+
+    assert_eq!(Exact::nom(&[2, 1, 2]).unwrap().1.value, Inherit(0x0201));
+    assert!(Exact::nom(&[3, 1, 2, 0]).is_err());
+
+    let long = Windowed {
+        len: 1,
+        padded: Inherit(0),
+        exact: Inherit(0),
+    };
+
+    assert!(long.write(&mut Vec::new()).is_err());
+}
+
+#[test]
+fn iter_until_end() {
+    let bytes = roundtrip(Repeated {
+        len: 4,
+        inner: vec![Inherit(1), Inherit(2)],
+        rest: vec![3, 4, 5],
+    });
+
+    assert_eq!(bytes, [4, 1, 0, 2, 0, 3, 4, 5]);
+
+    // Half an `Inherit` left in the window.
+    assert!(Repeated::nom(&[3, 1, 0, 2, 0]).is_err());
+}
+
+#[test]
+fn until_terminator() {
+    let bytes = roundtrip(Terminated {
+        name: b"ab".to_vec(),
+        code: vec![Op::Push(5), Op::End],
+        line: vec![7],
+        last: 9,
+    });
+
+    assert_eq!(bytes, [b'a', b'b', 0, 1, 5, 0, 7, 13, 10, 9]);
+
+    let (rest, dropped) = DroppedEnd::nom(&[1, 5, 0, 3]).unwrap();
+
+    assert_eq!(rest, [3]);
+    assert_eq!(dropped.code, [Op::Push(5)]);
+
+    // No terminator before the input runs out.
+    assert!(Terminated::nom(b"ab").is_err());
+}
+
+#[test]
+fn string_layouts() {
+    let bytes = roundtrip(Text {
+        prefixed: "hi".to_string(),
+        c_str: "ab".to_string(),
+        fixed: "abc".to_string(),
+        latin: "é".to_string(),
+        wide: "hé".to_string(),
+    });
+
+    assert_eq!(
+        bytes,
+        [
+            2, 0, b'h', b'i', b'a', b'b', 0, b'a', b'b', b'c', b' ', b' ', b' ', 0xE9, 0, 2, 0,
+            b'h', 0, 0xE9
+        ]
+    );
+
+    // Invalid UTF-8 in the length prefixed string.
+    assert!(Text::nom(&[1, 0, 0xFF, 0, b' ', b' ', b' ', b' ', b' ', b' ', 0, 0]).is_err());
+
+    // Latin-1 has no euro sign to write.
+    let euro = Text {
+        prefixed: String::new(),
+        c_str: String::new(),
+        fixed: String::new(),
+        latin: "€".to_string(),
+        wide: String::new(),
+    };
+
+    assert!(euro.write(&mut Vec::new()).is_err());
+
+    // Padding is stripped a whole UTF-16 code unit at a time.
+    let bytes = roundtrip(PaddedWide {
+        name: "A".to_string(),
+    });
+
+    assert_eq!(bytes, [0x41, 0, 0, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn zero_copy() {
+    let bytes = [3, 1, 2, 3, b'h', b'i', 0, 4, 5];
+    let (rest, borrowed) = Borrowed::nom(&bytes).unwrap();
+
+    assert!(rest.is_empty());
+    assert_eq!(borrowed.payload.as_ptr(), bytes[1..].as_ptr());
+    assert_eq!(borrowed.name, "hi");
+    assert_eq!(borrowed.pair, [4, 5]);
+
+    let mut out = Vec::new();
+    borrowed.write(&mut out).unwrap();
+
+    assert_eq!(out, bytes);
+
+    let (_, framed) = Framed::<Inherit>::nom(&[1, 0, 7, 8]).unwrap();
+
+    assert_eq!(framed.header, Inherit(1));
+    assert_eq!(framed.body, [7, 8]);
+}
+
+#[test]
+fn fixed_arrays() {
+    let bytes = roundtrip(Arrays {
+        mac: [1, 2, 3, 4, 5, 6],
+        regs: [Inherit(7), Inherit(8)],
+        table: vec![[9, 10], [11, 12]],
+    });
+
+    assert_eq!(bytes, [1, 2, 3, 4, 5, 6, 7, 0, 8, 0, 9, 10, 11, 12]);
+    assert!(Arrays::nom(&bytes[..5]).is_err());
+}
+
+#[test]
+fn primitive_fields() {
+    let bytes = roundtrip(Flags {
+        strict: true,
+        lenient: false,
+        letter: 'A',
+        pair: (1, Inherit(2)),
+        marker: std::marker::PhantomData,
+    });
+
+    assert_eq!(bytes, [1, 0, 0x41, 0, 0, 0, 1, 2, 0]);
+
+    let (_, flags) = Flags::nom(&[0, 0xFF, 0x41, 0, 0, 0, 1, 2, 0]).unwrap();
+
+    assert!(flags.lenient);
+    assert!(Flags::nom(&[0xFF, 0, 0x41, 0, 0, 0, 1, 2, 0]).is_err());
+}
+
+#[test]
+fn field_endian() {
+    let bytes = roundtrip(Mixed {
+        little: 1,
+        network: 2,
+        ports: vec![3],
+        host: 4,
+        after: 5,
+    });
+
+    assert_eq!(&bytes[..8], [1, 0, 0, 2, 0, 1, 0, 3]);
+    assert_eq!(bytes[8..12], 4u32.to_ne_bytes());
+    assert_eq!(&bytes[12..], [5, 0]);
+
+    assert_eq!(roundtrip(Record::Le(1)), [1, 1, 0]);
+    assert_eq!(roundtrip(Record::Be(1)), [2, 0, 1]);
+}
+
+#[test]
+fn runtime_endian() {
+    let bytes = [1, 0, 0, 1, 0, 2, 3, 0];
+    let (_, runtime) = Runtime::nom_ctx(&bytes, &Ctx::new(Endian::Big)).unwrap();
+
+    assert_eq!(runtime.version, 1);
+    assert_eq!(runtime.body, [2]);
+    assert_eq!(runtime.inner, Inherit(3));
+
+    let mut out = Vec::new();
+    runtime.write_ctx(&mut out, &Ctx::new(Endian::Big)).unwrap();
+
+    assert_eq!(out, bytes);
+
+    // Read little endian, the length prefix claims 256 elements.
+    assert!(Runtime::nom(&bytes).is_err());
+}
+
+#[test]
+fn nested_runtime_endian() {
+    let bytes = [0x4D, 0x4D, 0, 1, 1, 0, 2];
+    let (rest, tiff) = Tiff::nom_ctx(&bytes, &Ctx::new(Endian::Big)).unwrap();
+
+    assert!(rest.is_empty());
+    assert_eq!(tiff.ifd, Ifd { count: 1 });
+    assert_eq!(tiff.more, [Ifd { count: 2 }]);
+
+    let mut out = Vec::new();
+    tiff.write_ctx(&mut out, &Ctx::new(Endian::Big)).unwrap();
+
+    assert_eq!(out, bytes);
+
+    // Without a context the runtime order is the one `Tiff` was called with.
+    let (_, tiff) = Tiff::nom_endian(&bytes, Endian::Big).unwrap();
+
+    assert_eq!(tiff.ifd, Ifd { count: 1 });
+    assert_eq!(Tiff::nom(&bytes).unwrap().1.ifd, Ifd { count: 256 });
+}
+
+#[test]
+fn parent_ctx() {
+    let bytes = roundtrip(Parent {
+        version: 2,
+        flags: 1,
+        child: Versioned {
+            items: vec![7, 8],
+            extra: Some(9),
+        },
+    });
+
+    assert_eq!(bytes, [2, 1, 7, 8, 9, 0]);
+
+    let (rest, parent) = Parent::nom(&[1, 0, 7, 8]).unwrap();
+
+    assert_eq!(rest, [8]);
+    assert_eq!(
+        parent.child,
+        Versioned {
+            items: vec![7],
+            extra: None
+        }
+    );
+}
+
+#[test]
+fn cond_fields() {
+    let bytes = roundtrip(Optional {
+        flags: 2,
+        first: None,
+        second: Some(vec![5]),
+    });
+
+    assert_eq!(bytes, [2, 1, 5]);
+    assert_eq!(roundtrip(Extended::Item(1, None)), [1, 1]);
+    assert_eq!(roundtrip(Extended::Item(2, Some(3))), [1, 2, 3]);
+
+    // A `cond` field that holds is not optional any more.
+    assert!(Optional::nom(&[1, 0]).is_err());
+
+    let absent = Optional {
+        flags: 0,
+        first: Some(1),
+        second: None,
+    };
+
+    assert!(absent.write(&mut Vec::new()).is_err());
+}
+
+#[test]
+fn external_selector() {
+    let bytes = roundtrip(Resource {
+        rtype: 1,
+        data: Rdata::A([10, 0, 0, 1]),
+    });
+
+    assert_eq!(bytes, [1, 0, 10, 0, 0, 1]);
+
+    let (_, txt) = Resource::nom(&[99, 0, b'h', b'i']).unwrap();
+
+    assert_eq!(txt.data, Rdata::Txt(b"hi".to_vec()));
+    assert!(Resource::nom(&[2, 0, 1]).is_err());
+
+    let mismatched = Resource {
+        rtype: 16,
+        data: Rdata::A([0; 4]),
+    };
+
+    assert!(mismatched.write(&mut Vec::new()).is_err());
+}
+
+#[test]
+fn default_variant() {
+    assert_eq!(roundtrip(Message::Ping(7)), [1, 7, 0, 0, 0]);
+
+    let bytes = roundtrip(Message::Unknown {
+        tag: 0x42,
+        body: vec![1, 2],
+    });
+
+    assert_eq!(bytes, [0x42, 2, 0, 1, 2]);
+    assert!(Message::nom(&[0x42, 2, 0, 1]).is_err());
+}
+
+#[test]
+fn discriminant_fields() {
+    let (_, load) = Opcode::nom(&[10, 2]).unwrap();
+
+    assert_eq!(
+        load,
+        Opcode::Load {
+            opcode: 10,
+            slot: 2
+        }
+    );
+    assert_eq!(roundtrip(load), [10, 2]);
+    assert_eq!(roundtrip(Opcode::Call(1, 0x21)), [0x21, 1, 0]);
+
+    let wrong = Opcode::Load { opcode: 6, slot: 0 };
+
+    assert!(wrong.write(&mut Vec::new()).is_err());
+}
+
+#[test]
+fn range_arms() {
+    assert_eq!(Evm::nom(&[0x7F]).unwrap().1, Evm::Push(0x7F));
+    assert_eq!(Evm::nom(&[0x97]).unwrap().1, Evm::Stack);
+    assert!(Evm::nom(&[0x88]).is_err());
+    assert!(Evm::nom(&[0x98]).is_err());
+
+    assert_eq!(roundtrip(Evm::Push(0x61)), [0x61]);
+    assert_eq!(roundtrip(Evm::Stack), [0x80]);
+}
+
+#[test]
+fn rust_discriminants() {
+    assert_eq!(roundtrip(Branch::Nop), [1]);
+    assert_eq!(roundtrip(Branch::Else), [3]);
+    assert_eq!(roundtrip(Branch::End), [0x10]);
+    assert!(Branch::nom(&[0]).is_err());
+
+    assert_eq!(roundtrip(Frame::Data(5)), [0, 0, 0, 5]);
+    assert_eq!(roundtrip(Frame::Pong), [0, 0x11]);
+
+    assert_eq!(roundtrip(Wide::Zero), [0; 8]);
+    assert_eq!(roundtrip(Wide::Max), [0xFF; 8]);
+
+    assert_eq!(roundtrip(Signed::Minus), [0xFE]);
+    assert_eq!(roundtrip(Signed::Next), [2]);
+    assert_eq!(roundtrip(Signed::Back), [0xFF]);
+    assert!(Signed::nom(&[0]).is_err());
+}
+
+#[test]
+fn magic_tags() {
+    assert_eq!(
+        roundtrip(Chunk::Header {
+            width: 1,
+            height: 2
+        }),
+        b"IHDR\0\0\0\x01\0\0\0\x02"
+    );
+    assert_eq!(roundtrip(Chunk::End), b"IEND");
+    assert_eq!(roundtrip(Chunk::Short(7)), b"IE\x07");
+    assert_eq!(roundtrip(Chunk::Signature), b"\x89PNG");
+
+    let bytes = roundtrip(Chunk::Unknown {
+        tag: *b"tEXt",
+        data: vec![b'a'],
+    });
+
+    assert_eq!(bytes, b"tEXt\0\0\0\x01a");
+}
+
+#[test]
+fn generated_locals() {
+    let bytes = roundtrip(Locals {
+        input: 2,
+        rest: vec![3, 4],
+        start: "é".to_string(),
+        bytes: [5, 6],
+    });
+
+    assert_eq!(bytes, [2, 3, 4, 1, 0xE9, 5, 6]);
+
+    assert_eq!(roundtrip(Shadowed::Input { input: 7, disc: 8 }), [1, 7, 8]);
+    assert_eq!(roundtrip(Shadowed::Other { disc: 9, start: 10 }), [9, 10]);
+}
+
+#[test]
+fn shadowed_endian() {
+    let bytes = roundtrip(TiffHeader {
+        endian: 0x4D4D,
+        magic: 42,
+        offset: 8,
+    });
+
+    assert_eq!(bytes, b"MM\0\x2a\0\0\0\x08");
+
+    let bytes = roundtrip(Window {
+        size: 4,
+        window: "ab".to_string(),
+        out: 1,
+        fill: vec![2],
+    });
+
+    assert_eq!(bytes, b"\x04ab  \0\x01\x01\x02");
+}
@@ -0,0 +1,9 @@
+use structnom::StructNom;
+
+#[derive(StructNom)]
+#[snom(endian = middle)]
+pub struct Header {
+    data: u32,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use structnom::StructNom;
+
+#[derive(StructNom)]
+pub struct Huge {
+    #[snom(bits(129))]
+    value: u128,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use structnom::StructNom;
+
+#[derive(StructNom)]
+pub struct Header {
+    flags: u8,
+    #[snom(cond = flags != 0)]
+    extra: u16,
+}
+
+fn main() {}
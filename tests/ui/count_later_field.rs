@@ -0,0 +1,10 @@
+use structnom::StructNom;
+
+#[derive(StructNom)]
+pub struct Header {
+    #[snom(count = num)]
+    sections: Vec<u8>,
+    num: u16,
+}
+
+fn main() {}
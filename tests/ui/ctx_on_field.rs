@@ -0,0 +1,9 @@
+use structnom::StructNom;
+
+#[derive(StructNom)]
+pub struct Header {
+    #[snom(ctx = u8)]
+    version: u8,
+}
+
+fn main() {}
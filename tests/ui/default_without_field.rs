@@ -0,0 +1,12 @@
+use structnom::StructNom;
+
+#[derive(StructNom)]
+#[snom(switch = structnom::nom::number::complete::le_u8)]
+pub enum Opcode {
+    #[snom(val = 1)]
+    Nop,
+    #[snom(default)]
+    Unknown,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use structnom::StructNom;
+
+#[derive(StructNom)]
+pub struct Header {
+    #[snom(discriminant)]
+    kind: u8,
+}
+
+fn main() {}
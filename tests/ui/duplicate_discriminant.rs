@@ -0,0 +1,14 @@
+use structnom::StructNom;
+
+#[derive(StructNom)]
+#[snom(switch = structnom::nom::number::complete::le_u8)]
+pub enum Op {
+    #[snom(range(start = 1))]
+    Nop,
+    Add,
+    Sub,
+    #[snom(val = 0x02)]
+    Mul,
+}
+
+fn main() {}
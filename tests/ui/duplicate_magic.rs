@@ -0,0 +1,11 @@
+use structnom::StructNom;
+
+#[derive(StructNom)]
+pub enum Chunk {
+    #[snom(magic = b"IEND")]
+    End,
+    #[snom(magic = [0x49, 0x45, 0x4E, 0x44])]
+    Trailer,
+}
+
+fn main() {}
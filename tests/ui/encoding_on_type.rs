@@ -0,0 +1,9 @@
+use structnom::StructNom;
+
+#[derive(StructNom)]
+#[snom(encoding = latin1)]
+pub struct Name {
+    text: String,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use structnom::StructNom;
+
+#[derive(StructNom)]
+pub struct Header {
+    #[snom(parser = structnom::nom::number::complete::le_u8)]
+    #[snom(length = u16)]
+    data: u8,
+}
+
+fn main() {}
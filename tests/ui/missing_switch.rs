@@ -0,0 +1,9 @@
+use structnom::StructNom;
+
+#[derive(StructNom)]
+pub enum Op {
+    #[snom(val = 1)]
+    Nop,
+}
+
+fn main() {}
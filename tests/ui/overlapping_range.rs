@@ -0,0 +1,12 @@
+use structnom::StructNom;
+
+#[derive(StructNom)]
+#[snom(switch = structnom::nom::number::complete::le_u8)]
+pub enum Op {
+    #[snom(val = 0x12)]
+    Halt,
+    #[snom(range = 0x10..=0x1F)]
+    Load,
+}
+
+fn main() {}
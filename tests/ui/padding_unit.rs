@@ -0,0 +1,11 @@
+use structnom::StructNom;
+
+#[derive(StructNom)]
+pub struct Name {
+    #[snom(encoding = utf16le)]
+    #[snom(size = 8)]
+    #[snom(padding = [0x00])]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use structnom::StructNom;
+
+#[derive(StructNom)]
+pub struct Header {
+    #[snom(padding = b" ")]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use structnom::StructNom;
+
+#[derive(StructNom)]
+#[snom(switch = structnom::nom::number::complete::le_u8)]
+pub enum Op {
+    #[snom(range(start = 1))]
+    Nop,
+    Add,
+    #[snom(range(end = 7))]
+    Sub,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use structnom::StructNom;
+
+#[derive(StructNom)]
+pub union Either {
+    a: u32,
+    b: f32,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use structnom::StructNom;
+
+#[derive(StructNom)]
+pub struct Header {
+    #[snom(lenght = 4)]
+    data: u32,
+}
+
+fn main() {}
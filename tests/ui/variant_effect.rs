@@ -0,0 +1,11 @@
+use structnom::StructNom;
+
+#[derive(StructNom)]
+#[snom(switch = structnom::nom::number::complete::le_u8)]
+pub enum Op {
+    #[snom(val = 1)]
+    #[snom(take(2))]
+    Nop,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use structnom::StructNomWrite;
+
+#[derive(StructNomWrite)]
+pub struct Header {
+    #[snom(call(structnom::nom::number::complete::le_u32))]
+    version: u8,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use structnom::nom::number::complete::be_u16;
+use structnom::{StructNom, StructNomWrite};
+
+#[derive(StructNom, StructNomWrite)]
+pub struct Payload {
+    #[snom(length_parser = be_u16)]
+    data: Vec<u8>,
+}
+
+fn main() {}
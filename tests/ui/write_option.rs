@@ -0,0 +1,9 @@
+use structnom::{StructNom, StructNomWrite};
+
+#[derive(StructNom, StructNomWrite)]
+pub struct Record {
+    a: Option<u8>,
+    b: Vec<u8>,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use structnom::nom::number::complete::be_u32;
+use structnom::{StructNom, StructNomWrite};
+
+#[derive(StructNom, StructNomWrite)]
+pub struct Header {
+    #[snom(parser = be_u32)]
+    magic: u32,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use structnom::nom::IResult;
+use structnom::{StructNom, StructNomWrite};
+
+fn opcode(input: &[u8]) -> IResult<&[u8], u8> {
+    structnom::nom::number::complete::le_u8(input)
+}
+
+#[derive(StructNom, StructNomWrite)]
+#[snom(switch = opcode)]
+pub enum Op {
+    #[snom(val = 1)]
+    Nop,
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+use structnom::nom::number::complete::le_u8;
+use structnom::{StructNom, StructNomWrite};
+
+#[derive(Debug, PartialEq, StructNom, StructNomWrite)]
+#[snom(switch = le_u8)]
+pub enum Op {
+    #[snom(val = 0)]
+    End,
+    #[snom(val = 1)]
+    Nop,
+}
+
+#[derive(StructNom, StructNomWrite)]
+pub struct Code {
+    #[snom(until = Op::End)]
+    ops: Vec<Op>,
+}
+
+fn main() {}